@@ -0,0 +1,49 @@
+//! Compares the per-instruction [`standard_decoder`] against the
+//! table-driven [`TableDecoder`]/[`decode_region`] over a large batch of
+//! bytecode, the workload an evolver scanning candidate cores would drive.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use marzipan_core::{
+    emulators::generic_emulator::{decode_region, standard_decoder, Emulator},
+    EmulatorCore,
+};
+use redcode::{test_utils::all_instructions, Standard};
+
+/// Number of bytecode values decoded per benchmark iteration.
+const BATCH_SIZE: usize = 8192;
+
+/// Encodes a batch of redcode instructions to raw bytecode via a throwaway
+/// [`Emulator`], since [`Emulator::rc_to_bytecode`] is the only public way
+/// to get from a [`redcode::Instruction`] to this emulator's bytecode.
+fn bytecode_batch() -> Vec<u32> {
+    let emulator =
+        Emulator::new(1, 0, 1, 1, 1, 1).expect("valid core settings");
+    all_instructions()
+        .cycle()
+        .take(BATCH_SIZE)
+        .map(|instr| emulator.rc_to_bytecode(instr))
+        .collect()
+}
+
+fn bench_decode(c: &mut Criterion) {
+    let bytecode = bytecode_batch();
+    let decoder = standard_decoder(Standard::PMarsExtended);
+
+    c.bench_function("standard_decoder, one instruction at a time", |b| {
+        b.iter(|| {
+            for &word in &bytecode {
+                black_box(decoder.decode(black_box(word)));
+            }
+        });
+    });
+
+    c.bench_function("decode_region, table-driven batch", |b| {
+        let mut out = vec![None; bytecode.len()];
+        b.iter(|| {
+            decode_region(black_box(&bytecode), black_box(&mut out));
+        });
+    });
+}
+
+criterion_group!(benches, bench_decode);
+criterion_main!(benches);
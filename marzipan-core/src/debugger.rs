@@ -0,0 +1,232 @@
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    ops::RangeInclusive,
+    rc::Rc,
+};
+
+use redcode::Instruction;
+
+use crate::{
+    emulator_core::{CoreObserver, EmulatorCore, EmulatorResult},
+    CoreAddr,
+};
+
+/// Which kind of registered point a [`DebugEvent`] fired for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DebugEventKind {
+    /// A registered breakpoint address was about to execute
+    Breakpoint,
+    /// A registered read-watchpoint address was read while evaluating an
+    /// instruction's operands
+    ReadWatchpoint,
+    /// A registered write-watchpoint address was written
+    WriteWatchpoint,
+}
+
+/// One triggered breakpoint or watchpoint, reported by [`Debugger::step`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DebugEvent {
+    /// The warrior whose instruction triggered this event
+    pub warrior_id: u64,
+    /// The program counter executing when this event fired
+    pub pc: CoreAddr,
+    /// The core address the registered point matched: equal to `pc` for a
+    /// [`DebugEventKind::Breakpoint`], or the read/written address for a
+    /// watchpoint
+    pub addr: CoreAddr,
+    /// Which kind of registered point fired
+    pub kind: DebugEventKind,
+}
+
+/// Registered breakpoints/watchpoints and the state [`DebuggerHook`] and
+/// [`Debugger`] both need to see, shared between them since one is owned by
+/// the attached [`EmulatorCore`] and the other is held by the host.
+#[derive(Default)]
+struct DebugState {
+    breakpoints: HashSet<CoreAddr>,
+    read_watchpoints: Vec<RangeInclusive<CoreAddr>>,
+    write_watchpoints: Vec<RangeInclusive<CoreAddr>>,
+    step_counts: HashMap<u64, u64>,
+    /// The pc most recently passed to `on_step` for each warrior, so a
+    /// matching `on_read`/`on_write` (which only get an address) can still
+    /// report which instruction caused it.
+    last_pc: HashMap<u64, CoreAddr>,
+    /// The breakpoint address each warrior was last stopped at, so the next
+    /// [`Debugger::step`] for that warrior runs the instruction there instead
+    /// of reporting the same breakpoint again.
+    halted: HashMap<u64, CoreAddr>,
+    /// Watchpoint hits collected since the last [`Debugger::step`] drained
+    /// them.
+    pending: Vec<DebugEvent>,
+}
+
+impl DebugState {
+    fn record_watchpoint_if_matched(
+        &mut self,
+        hit: bool,
+        kind: DebugEventKind,
+        warrior_id: u64,
+        addr: CoreAddr,
+    ) {
+        if hit {
+            let pc = self.last_pc.get(&warrior_id).copied().unwrap_or(addr);
+            self.pending.push(DebugEvent { warrior_id, pc, addr, kind });
+        }
+    }
+}
+
+/// The [`CoreObserver`] half of a [`Debugger`], attached to an
+/// [`EmulatorCore`] with [`Debugger::attach`]. Kept separate from
+/// [`Debugger`] itself because `attach_observer` takes ownership of a
+/// `Box<dyn CoreObserver>`, while the host still needs to register points and
+/// read back triggered events through the `Debugger` it kept.
+struct DebuggerHook(Rc<RefCell<DebugState>>);
+
+impl CoreObserver for DebuggerHook {
+    fn on_step(
+        &mut self,
+        warrior_id: u64,
+        pc: CoreAddr,
+        _executed: Instruction,
+    ) {
+        let mut state = self.0.borrow_mut();
+        let count = state.step_counts.entry(warrior_id).or_insert(0);
+        *count = count.saturating_add(1);
+        state.last_pc.insert(warrior_id, pc);
+    }
+
+    fn on_death(&mut self, _warrior_id: u64) {}
+
+    fn on_write(&mut self, warrior_id: u64, addr: CoreAddr) {
+        let mut state = self.0.borrow_mut();
+        let hit = state
+            .write_watchpoints
+            .iter()
+            .any(|range| range.contains(&addr));
+        state.record_watchpoint_if_matched(
+            hit,
+            DebugEventKind::WriteWatchpoint,
+            warrior_id,
+            addr,
+        );
+    }
+
+    fn on_read(&mut self, warrior_id: u64, addr: CoreAddr) {
+        let mut state = self.0.borrow_mut();
+        let hit = state
+            .read_watchpoints
+            .iter()
+            .any(|range| range.contains(&addr));
+        state.record_watchpoint_if_matched(
+            hit,
+            DebugEventKind::ReadWatchpoint,
+            warrior_id,
+            addr,
+        );
+    }
+}
+
+/// A breakpoint/watchpoint debugging layer on top of any [`EmulatorCore`].
+///
+/// `EmulatorCore` itself only exposes `read_core`/`read_process_queue` for
+/// inspection; this builds the interactive-debugger surface the design docs
+/// call for on top of that, reusing [`CoreObserver`] rather than adding a
+/// second instrumentation mechanism. Watchpoints therefore see what those
+/// hooks see: every write an executed opcode makes, and the reads of each
+/// executed instruction and its two operand targets. Writes made by
+/// predecrement/postincrement operand evaluation, and reads of the
+/// intermediate pointer cells, are not reported.
+///
+/// Register points, call [`Debugger::attach`] once, then drive the emulator
+/// through [`Debugger::step`] instead of [`EmulatorCore::step`] directly.
+#[derive(Default)]
+pub struct Debugger {
+    state: Rc<RefCell<DebugState>>,
+}
+
+impl Debugger {
+    /// Constructs a debugger with no registered breakpoints or watchpoints.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Attaches this debugger's observer half to `emulator`. Replaces any
+    /// observer already attached, per [`EmulatorCore::attach_observer`].
+    pub fn attach(&self, emulator: &mut dyn EmulatorCore) {
+        let hook = DebuggerHook(Rc::clone(&self.state));
+        emulator.attach_observer(Box::new(hook));
+    }
+
+    /// Registers an execute-breakpoint: [`Debugger::step`] reports this
+    /// address instead of running anything once a warrior's next instruction
+    /// is here.
+    pub fn add_breakpoint(&self, addr: CoreAddr) {
+        self.state.borrow_mut().breakpoints.insert(addr);
+    }
+
+    /// Removes a previously registered breakpoint. A no-op if it wasn't set.
+    pub fn remove_breakpoint(&self, addr: CoreAddr) {
+        self.state.borrow_mut().breakpoints.remove(&addr);
+    }
+
+    /// Registers a read-watchpoint: [`Debugger::step`] reports a
+    /// [`DebugEventKind::ReadWatchpoint`] event the next time any address in
+    /// `range` is read while evaluating an instruction's operands.
+    pub fn add_read_watchpoint(&self, range: RangeInclusive<CoreAddr>) {
+        self.state.borrow_mut().read_watchpoints.push(range);
+    }
+
+    /// Registers a write-watchpoint: [`Debugger::step`] reports a
+    /// [`DebugEventKind::WriteWatchpoint`] event the next time any address
+    /// in `range` is written.
+    pub fn add_write_watchpoint(&self, range: RangeInclusive<CoreAddr>) {
+        self.state.borrow_mut().write_watchpoints.push(range);
+    }
+
+    /// Returns the number of instructions this warrior has executed while
+    /// this debugger's observer was attached, whether driven through
+    /// [`Debugger::step`] or by stepping/running the emulator directly.
+    pub fn step_count(&self, warrior_id: u64) -> u64 {
+        self.state
+            .borrow()
+            .step_counts
+            .get(&warrior_id)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Executes `warrior_id`'s next instruction on `emulator`, unless it's
+    /// about to land on a registered breakpoint, in which case this reports
+    /// that breakpoint and runs nothing, leaving the instruction to execute
+    /// on the next call once the host resumes. Otherwise runs it and returns
+    /// every read/write-watchpoint it triggered.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::EmulatorError`]s from the underlying
+    /// [`EmulatorCore::process_queue_into`]/[`EmulatorCore::step`].
+    pub fn step(
+        &self,
+        emulator: &mut dyn EmulatorCore,
+        warrior_id: u64,
+    ) -> EmulatorResult<Vec<DebugEvent>> {
+        let mut queue = Vec::new();
+        emulator.process_queue_into(warrior_id, &mut queue)?;
+        if let Some(&next_pc) = queue.first() {
+            let mut state = self.state.borrow_mut();
+            let resuming = state.halted.remove(&warrior_id) == Some(next_pc);
+            if !resuming && state.breakpoints.contains(&next_pc) {
+                state.halted.insert(warrior_id, next_pc);
+                return Ok(vec![DebugEvent {
+                    warrior_id,
+                    pc: next_pc,
+                    addr: next_pc,
+                    kind: DebugEventKind::Breakpoint,
+                }]);
+            }
+        }
+        emulator.step(warrior_id)?;
+        Ok(self.state.borrow_mut().pending.drain(..).collect())
+    }
+}
@@ -1,4 +1,6 @@
-use alloc::fmt;
+use alloc::{boxed::Box, fmt};
+
+use redcode::Standard;
 
 use crate::{BytecodeInstructionIdentifier, CoreAddr};
 
@@ -46,13 +48,149 @@ pub trait EmulatorCore {
         warriors_remaining: u64,
     ) -> EmulatorResult<u64>;
 
+    /// Execute up to `budget` cycles, stopping early the first time any
+    /// trigger in `stop` fires.  Returns the number of cycles executed and
+    /// the [`StopReason`] that ended the run.
+    ///
+    /// Conditions are only checked between cycles, using existing
+    /// [`EmulatorCore`] primitives ([`EmulatorCore::step`],
+    /// [`EmulatorCore::active_warriors_into`], [`EmulatorCore::read_core`],
+    /// [`EmulatorCore::process_queue_into`]).  A default-constructed
+    /// [`StopConditions`] disables every trigger, so this behaves like
+    /// [`EmulatorCore::run`] with an unreachable `warriors_remaining`, and the
+    /// per-cycle overhead it adds is limited to the checks each set field
+    /// requires.
+    ///
+    /// If more than one trigger fires on the same cycle, the one checked
+    /// first among: cycle budget, `warriors_remaining`, per-warrior death
+    /// triggers (in the order warriors were stepped), then watchpoints (in
+    /// list order) is reported.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EmulatorError`]s for invalid inputs (e.g. an out of range
+    /// watchpoint address) or in the event of an internal error.
+    fn run_until(
+        &mut self,
+        budget: u64,
+        stop: &StopConditions,
+    ) -> EmulatorResult<(u64, StopReason)> {
+        let mut watch_values = Vec::with_capacity(stop.watchpoints.len());
+        for &addr in &stop.watchpoints {
+            watch_values.push(self.read_core(addr)?);
+        }
+        let mut active = Vec::new();
+        let mut queue_buf = Vec::new();
+        let mut cycles_executed = 0;
+        loop {
+            if cycles_executed >= budget {
+                return Ok((cycles_executed, StopReason::CycleBudgetExhausted));
+            }
+            self.active_warriors_into(&mut active);
+            if let Some(target) = stop.warriors_remaining {
+                if active.len() as u64 <= target {
+                    return Ok((cycles_executed, StopReason::WarriorsRemaining));
+                }
+            }
+            let mut died = None;
+            for w in active.iter().copied() {
+                self.step(w)?;
+                if died.is_none()
+                    && (stop.warrior_death == Some(w) || stop.any_queue_emptied)
+                {
+                    self.process_queue_into(w, &mut queue_buf)?;
+                    if queue_buf.is_empty() {
+                        died = Some(if stop.warrior_death == Some(w) {
+                            StopReason::WarriorDied(w)
+                        } else {
+                            StopReason::QueueEmptied(w)
+                        });
+                    }
+                }
+            }
+            if let Some(reason) = died {
+                return Ok((cycles_executed.saturating_add(1), reason));
+            }
+            for (value, &addr) in
+                watch_values.iter_mut().zip(&stop.watchpoints)
+            {
+                let now = self.read_core(addr)?;
+                if *value != now {
+                    return Ok((
+                        cycles_executed.saturating_add(1),
+                        StopReason::Watchpoint(addr),
+                    ));
+                }
+                *value = now;
+            }
+            cycles_executed = cycles_executed.saturating_add(1);
+        }
+    }
+
+    /// Execute up to `cycles` cycles, or until the count of active warriors
+    /// reaches `warriors_remaining`, pausing every `quotient` executed
+    /// cycles to let `on_quotient` inspect progress and decide whether to
+    /// keep going.
+    ///
+    /// This is [`EmulatorCore::run`] with a host-driven checkpoint instead
+    /// of a single blocking call: a GUI can redraw core state between
+    /// checkpoints, a tournament runner can enforce a wall-clock deadline,
+    /// and a debugger can single-step by passing `quotient = 1`, all
+    /// without reimplementing round-robin scheduling on top of
+    /// [`EmulatorCore::step`]. `quotient = 0` disables the checkpoint
+    /// entirely, behaving like [`EmulatorCore::run`].
+    ///
+    /// Like [`EmulatorCore::run_until`], this is a default method built
+    /// from [`EmulatorCore::step`] and [`EmulatorCore::active_warriors_into`],
+    /// so checkpoints are only observed between cycles.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EmulatorError`]s for invalid inputs or in the event of an
+    /// internal error.
+    fn run_with_observer(
+        &mut self,
+        cycles: u64,
+        warriors_remaining: u64,
+        quotient: u64,
+        on_quotient: &mut dyn FnMut(u64, &[u64]) -> RunControl,
+    ) -> EmulatorResult<(u64, RunOutcome)> {
+        let mut active = Vec::new();
+        let mut cycles_executed: u64 = 0;
+        loop {
+            if cycles_executed >= cycles {
+                return Ok((cycles_executed, RunOutcome::CycleBudgetExhausted));
+            }
+            self.active_warriors_into(&mut active);
+            if active.len() as u64 <= warriors_remaining {
+                return Ok((cycles_executed, RunOutcome::WarriorsRemaining));
+            }
+            for w in active.iter().copied() {
+                self.step(w)?;
+            }
+            cycles_executed = cycles_executed.saturating_add(1);
+            if quotient != 0 && cycles_executed % quotient == 0 {
+                self.active_warriors_into(&mut active);
+                match on_quotient(cycles_executed, &active) {
+                    RunControl::Continue => {}
+                    RunControl::Pause => {
+                        return Ok((cycles_executed, RunOutcome::Paused))
+                    }
+                    RunControl::Abort => {
+                        return Ok((cycles_executed, RunOutcome::Aborted))
+                    }
+                }
+            }
+        }
+    }
+
     /// Query per-core settings such as `bytecode_format` and `core_size`.
     ///
     /// [`EmulatorCore`]s are classified by [`CoreSettings`] which
     /// aren't expected to be configurable through the [`EmulatorCore`] trait.
     /// Users of [`EmulatorCore`] might need to configure a new emulator or
     /// dispatch to different emulators depending on the required settings.
-    fn core_settings(self) -> CoreSettings;
+    fn core_settings(&self) -> CoreSettings;
 
     /// Query the value stored at an address in the core.
     ///
@@ -85,6 +223,73 @@ pub trait EmulatorCore {
         b_field: CoreAddr,
     ) -> EmulatorResult<()>;
 
+    /// Reads a contiguous, wrapping block of core cells in one call.
+    ///
+    /// Equivalent to calling [`EmulatorCore::read_core`] once per address,
+    /// starting at `start` and wrapping modulo `core_size` for
+    /// `out.len()` cells. Implementations backed by a flat buffer should
+    /// override this to validate the whole range once and copy directly; the
+    /// default here just loops over [`EmulatorCore::read_core`], which
+    /// bounds-checks each cell individually, so every
+    /// implementation gets a working (if not maximally fast) version for
+    /// free.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EmulatorError`]s for invalid inputs or in the event of an
+    /// internal error.
+    fn read_core_range(
+        &self,
+        start: CoreAddr,
+        out: &mut [(BytecodeInstructionIdentifier, CoreAddr, CoreAddr)],
+    ) -> EmulatorResult<()> {
+        let core_size = self.core_settings().core_size;
+        for (idx, slot) in out.iter_mut().enumerate() {
+            let offset = u64::try_from(idx).map_or(
+                Err(EmulatorError::InternalError(
+                    "read_core_range offset doesn't fit a u64",
+                )),
+                Ok,
+            )?;
+            *slot = self.read_core(wrap_addr(start, offset, core_size)?)?;
+        }
+        Ok(())
+    }
+
+    /// Writes a contiguous, wrapping block of core cells in one call.
+    ///
+    /// Equivalent to calling [`EmulatorCore::write_core`] once per address,
+    /// starting at `start` and wrapping modulo `core_size` for
+    /// `cells.len()` cells. Implementations backed by a flat buffer should
+    /// override this to validate the whole range once and copy directly; the
+    /// default here just loops over [`EmulatorCore::write_core`], which
+    /// bounds-checks each cell individually, so every
+    /// implementation gets a working (if not maximally fast) version for
+    /// free.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EmulatorError`]s for invalid inputs or in the event of an
+    /// internal error.
+    fn write_core_range(
+        &mut self,
+        start: CoreAddr,
+        cells: &[(BytecodeInstructionIdentifier, CoreAddr, CoreAddr)],
+    ) -> EmulatorResult<()> {
+        let core_size = self.core_settings().core_size;
+        for (idx, &(insn, a_field, b_field)) in cells.iter().enumerate() {
+            let offset = u64::try_from(idx).map_or(
+                Err(EmulatorError::InternalError(
+                    "write_core_range offset doesn't fit a u64",
+                )),
+                Ok,
+            )?;
+            let addr = wrap_addr(start, offset, core_size)?;
+            self.write_core(addr, insn, a_field, b_field)?;
+        }
+        Ok(())
+    }
+
     /// Read a value from the PSPACE owned by some warrior.
     ///
     /// PSPACE support, allocations, and PIN assignments are defined by the
@@ -138,12 +343,62 @@ pub trait EmulatorCore {
         initial_b: CoreAddr,
     ) -> EmulatorResult<()>;
 
+    /// Like [`Self::reset_core`], but leaves existing PSPACE mappings and
+    /// values alone instead of wiping them.
+    ///
+    /// A MARS runs a battle as several rounds sharing one PSPACE, so that
+    /// warriors can carry strategy state between rounds; this is what it
+    /// should call to set up each round after the first, writing the new
+    /// round's per-warrior result code into address zero itself (see the
+    /// module docs' note on PSPACE persistence) rather than starting every
+    /// warrior over with an empty PSPACE as [`Self::reset_core`] would.
+    ///
+    /// This rewrites every cell, so it costs `O(core_size)` regardless of how
+    /// much of the core the previous round actually touched. A tournament
+    /// replaying the same fill across hundreds of rounds on a large core
+    /// should instead bracket each round with [`Self::snapshot`] and
+    /// [`Self::rollback`], which cost `O(cells touched)`: the journal records
+    /// at most one undo per cell written during the round, however many
+    /// times it was rewritten (e.g. by a tight self-modifying loop).
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`EmulatorError::InvalidParam`] if any part of the new
+    /// initial instruction is invalid for the core settings active for this
+    /// emulator.
+    fn reset_core_keep_pspace(
+        &mut self,
+        initial_instr: BytecodeInstructionIdentifier,
+        initial_a: CoreAddr,
+        initial_b: CoreAddr,
+    ) -> EmulatorResult<()>;
+
     /// Returns the set of warriors with non-empty process queues.
-    fn active_warrior_set(&self) -> Vec<u64>;
+    ///
+    /// This allocates a fresh `Vec` on every call.  Hot loops that call this
+    /// repeatedly (e.g. evolvers or tournaments running many generations)
+    /// should prefer [`EmulatorCore::active_warriors_into`] to reuse a buffer.
+    fn active_warrior_set(&self) -> Vec<u64> {
+        let mut out = Vec::new();
+        self.active_warriors_into(&mut out);
+        out
+    }
+
+    /// Writes the set of warriors with non-empty process queues into `out`,
+    /// clearing it first, and returns the number of warriors written.
+    ///
+    /// Reuses `out`'s existing allocation instead of allocating a fresh `Vec`,
+    /// so callers that invoke this in a hot loop can amortize one allocation
+    /// across the whole run.
+    fn active_warriors_into(&self, out: &mut Vec<u64>) -> usize;
 
     /// Returns a copy of the process queue for a warrior.  This will be empty
     /// for inactive warriors.  Otherwise the next process to execute is first.
     ///
+    /// This allocates a fresh `Vec` on every call.  Hot loops that call this
+    /// repeatedly (e.g. evolvers or tournaments running many generations)
+    /// should prefer [`EmulatorCore::process_queue_into`] to reuse a buffer.
+    ///
     /// # Errors
     ///
     /// Returns [`EmulatorError`]s for invalid inputs or in the event of an
@@ -151,7 +406,30 @@ pub trait EmulatorCore {
     fn read_process_queue(
         &self,
         warrior_id: u64,
-    ) -> EmulatorResult<Vec<CoreAddr>>;
+    ) -> EmulatorResult<Vec<CoreAddr>> {
+        let mut out = Vec::new();
+        self.process_queue_into(warrior_id, &mut out)?;
+        Ok(out)
+    }
+
+    /// Writes the process queue for a warrior into `out`, clearing it first,
+    /// and returns the number of entries written.  This will write nothing
+    /// for inactive warriors.  Otherwise the next process to execute is
+    /// first.
+    ///
+    /// Reuses `out`'s existing allocation instead of allocating a fresh `Vec`,
+    /// so callers that invoke this in a hot loop can amortize one allocation
+    /// across the whole run.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EmulatorError`]s for invalid inputs or in the event of an
+    /// internal error.
+    fn process_queue_into(
+        &self,
+        warrior_id: u64,
+        out: &mut Vec<CoreAddr>,
+    ) -> EmulatorResult<usize>;
 
     /// Replaces the warriors current processes with the values in the input.
     ///
@@ -192,12 +470,240 @@ pub trait EmulatorCore {
         &self,
         redcode_instr: redcode::Instruction,
     ) -> BytecodeInstructionIdentifier;
+
+    /// Captures all observable state (core cells, every process queue,
+    /// partial-cycle state, and PSPACE values/PIN mapping) so it can later be
+    /// restored with [`EmulatorCore::rollback`].
+    ///
+    /// Intended for optimizers, evolvers, and battle-tree search that
+    /// repeatedly run a warrior from a state, observe the outcome, and
+    /// restore.  Implementations are expected to journal mutations rather
+    /// than copy the full state, so snapshotting and rolling back cost
+    /// roughly proportional to the state touched since the snapshot, not to
+    /// `core_size`.
+    ///
+    /// Snapshots nest: taking another snapshot before rolling back the first
+    /// is supported, and rolling back to an outer [`SnapshotToken`] discards
+    /// any inner ones.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EmulatorError`]s in the event of an internal error.
+    fn snapshot(&mut self) -> EmulatorResult<SnapshotToken>;
+
+    /// Restores all observable state to how it was when `token` was returned
+    /// by [`EmulatorCore::snapshot`], undoing every mutation made since.
+    ///
+    /// Rolling back to an outer token discards any snapshots taken after it;
+    /// their tokens are no longer valid for a later rollback.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EmulatorError::InvalidParam`] if `token` has already been
+    /// consumed by a rollback, came from a different emulator instance, or
+    /// otherwise isn't currently active.
+    fn rollback(&mut self, token: SnapshotToken) -> EmulatorResult<()>;
+
+    /// Attaches an observer that receives callbacks as this emulator executes
+    /// instructions, replacing any previously attached observer.
+    ///
+    /// See [`CoreObserver`].  Hooks fire from inside [`EmulatorCore::step`]
+    /// (and transitively [`EmulatorCore::run`]/[`EmulatorCore::run_until`]).
+    /// Detach with [`EmulatorCore::detach_observer`] to stop paying for them
+    /// on the hot path.
+    fn attach_observer(&mut self, obs: Box<dyn CoreObserver>);
+
+    /// Detaches and drops any observer attached with
+    /// [`EmulatorCore::attach_observer`].  A no-op if none is attached.
+    fn detach_observer(&mut self);
+
+    /// Captures this emulator's entire observable state into a
+    /// self-describing blob: a header of `bytecode_format`, `core_size`,
+    /// `pspace_size`, `warriors`, and `processes`, followed by every core
+    /// cell's `(bytecode, a_field, b_field)` triple, every warrior's process
+    /// queue, and all PSPACE contents.
+    ///
+    /// Pairs with [`EmulatorCore::deserialize_state`] to save/restore
+    /// in-progress battles, or to distribute evolver populations between
+    /// machines running compatible emulators (i.e. ones sharing a
+    /// `bytecode_format`).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EmulatorError`]s in the event of an internal error.
+    fn serialize_state(&self) -> EmulatorResult<Vec<u8>>;
+
+    /// Replaces this emulator's entire observable state with a blob produced
+    /// by [`EmulatorCore::serialize_state`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EmulatorError::UnsupportedFeature`] if the blob's own
+    /// `bytecode_format` is `None` or empty, since its raw bytecode
+    /// identifiers aren't guaranteed portable even to another instance of
+    /// the same implementation. Returns [`EmulatorError::InvalidParam`] if
+    /// the embedded `bytecode_format` or core geometry (`core_size`,
+    /// `pspace_size`, `warriors`, `processes`) disagrees with this
+    /// emulator's, or if `bytes` is truncated or malformed.
+    fn deserialize_state(&mut self, bytes: &[u8]) -> EmulatorResult<()>;
 }
 
-/// Configurations applied to an emulator.  
+/// Receives callbacks for execution events as an [`EmulatorCore`] runs,
+/// without polling core state every cycle.
+///
+/// Attach with [`EmulatorCore::attach_observer`].  Implementations should be
+/// cheap, since hooks fire on the per-instruction path.
+pub trait CoreObserver {
+    /// Called immediately before `executed` is run for `warrior_id` at `pc`
+    fn on_step(
+        &mut self,
+        warrior_id: u64,
+        pc: CoreAddr,
+        executed: redcode::Instruction,
+    );
+
+    /// Called when `warrior_id`'s process queue becomes empty
+    fn on_death(&mut self, warrior_id: u64);
+
+    /// Called when `warrior_id` writes to `addr` in the core, just before the
+    /// new value is stored.
+    ///
+    /// Only writes made by the executed opcode are reported; predecrement/
+    /// postincrement writes made while evaluating operands are not.
+    fn on_write(&mut self, warrior_id: u64, addr: CoreAddr);
+
+    /// Called when `warrior_id` reads `addr` from the core while evaluating
+    /// an instruction's operands, i.e. once each for the instruction at the
+    /// program counter and its A- and B-pointer targets.
+    ///
+    /// Defaults to a no-op so existing observers don't need to implement it.
+    fn on_read(&mut self, _warrior_id: u64, _addr: CoreAddr) {}
+
+    /// Called when `warrior_id` enqueues `addr` to its process queue
+    ///
+    /// Defaults to a no-op so existing observers don't need to implement it.
+    fn on_enqueue(&mut self, _warrior_id: u64, _addr: CoreAddr) {}
+}
+
+/// Computes `(start + offset) % core_size` as a [`CoreAddr`].
+///
+/// Used by the default [`EmulatorCore::read_core_range`]/
+/// [`EmulatorCore::write_core_range`] implementations to wrap a contiguous
+/// range around the end of the core, and by [`crate::loader::load_warrior`]
+/// to place a warrior's first process relative to its load address.
+///
+/// # Errors
+///
+/// Returns an [`EmulatorError::InvalidParam`] if `core_size` is zero, or an
+/// [`EmulatorError::InternalError`] if the arithmetic doesn't fit.
+pub(crate) fn wrap_addr(
+    start: CoreAddr,
+    offset: u64,
+    core_size: u64,
+) -> EmulatorResult<CoreAddr> {
+    let sum = u64::from(start).checked_add(offset).ok_or(
+        EmulatorError::InternalError(
+            "core_range offset overflowed while wrapping",
+        ),
+    )?;
+    let wrapped = sum.checked_rem(core_size).ok_or(EmulatorError::InvalidParam(
+        "core_size is zero, so no address is valid",
+    ))?;
+    CoreAddr::try_from(wrapped).map_or(
+        Err(EmulatorError::InternalError(
+            "wrapped core address doesn't fit a CoreAddr",
+        )),
+        Ok,
+    )
+}
+
+/// An opaque handle identifying a point [`EmulatorCore::rollback`] can later
+/// restore to.
+///
+/// Obtained from [`EmulatorCore::snapshot`].  A token that has already been
+/// consumed by a rollback, or that came from a different emulator instance,
+/// isn't valid; using it returns [`EmulatorError::InvalidParam`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SnapshotToken(pub(crate) u64);
+
+/// Optional early-stop triggers for [`EmulatorCore::run_until`].
+///
+/// Every field defaults to disabled, so `StopConditions::default()` runs
+/// until the cycle budget is exhausted.
+#[derive(Debug, Clone, Default)]
+pub struct StopConditions {
+    /// Core addresses to watch.  [`EmulatorCore::run_until`] fires
+    /// [`StopReason::Watchpoint`] the first cycle any of these cells' value
+    /// differs from what it was when the call started (or, if it also
+    /// changed on an earlier cycle of the same call, from its value as of
+    /// the end of that cycle).
+    pub watchpoints: Vec<CoreAddr>,
+
+    /// Fires [`StopReason::WarriorsRemaining`] the first cycle the number of
+    /// warriors with a non-empty process queue drops to this value or below.
+    pub warriors_remaining: Option<u64>,
+
+    /// Fires [`StopReason::WarriorDied`] the first cycle this warrior's
+    /// process queue becomes empty.
+    pub warrior_death: Option<u64>,
+
+    /// Fires [`StopReason::QueueEmptied`] the first cycle any warrior's
+    /// process queue becomes empty, identifying which one.
+    pub any_queue_emptied: bool,
+}
+
+/// Reports which trigger in a [`StopConditions`] ended an
+/// [`EmulatorCore::run_until`] call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    /// `budget` cycles were executed without any other trigger firing
+    CycleBudgetExhausted,
+    /// The value at this watchpoint address changed
+    Watchpoint(CoreAddr),
+    /// The number of active warriors reached [`StopConditions::warriors_remaining`]
+    WarriorsRemaining,
+    /// The warrior identified by [`StopConditions::warrior_death`] ran out of
+    /// processes
+    WarriorDied(u64),
+    /// This warrior's process queue became empty, triggered by
+    /// [`StopConditions::any_queue_emptied`]
+    QueueEmptied(u64),
+}
+
+/// Host decision returned from the callback passed to
+/// [`EmulatorCore::run_with_observer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunControl {
+    /// Keep running until the next checkpoint, cycle budget, or
+    /// `warriors_remaining`.
+    Continue,
+    /// Stop now; [`EmulatorCore::run_with_observer`] returns
+    /// [`RunOutcome::Paused`] with the cycles executed so far.
+    Pause,
+    /// Stop now; [`EmulatorCore::run_with_observer`] returns
+    /// [`RunOutcome::Aborted`] with the cycles executed so far.
+    Abort,
+}
+
+/// Reports why an [`EmulatorCore::run_with_observer`] call returned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunOutcome {
+    /// `cycles` cycles were executed without the callback pausing or
+    /// aborting
+    CycleBudgetExhausted,
+    /// The number of active warriors reached `warriors_remaining`
+    WarriorsRemaining,
+    /// The callback returned [`RunControl::Pause`]
+    Paused,
+    /// The callback returned [`RunControl::Abort`]
+    Aborted,
+}
+
+/// Configurations applied to an emulator.
 ///
 /// These are typically configured when an emulator is constructed and
-/// static through the lifetime of an emulator object.  
+/// static through the lifetime of an emulator object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct CoreSettings {
     /// Number of addresses in the core.  All fields are modulo `core_size`
     pub core_size: u64,
@@ -225,6 +731,25 @@ pub struct CoreSettings {
     /// <https://corewar.co.uk/standards/icws94.htm#5.5.14>
     pub processes: u64,
 
+    /// The width of the window, centered on the program counter, that a
+    /// read (any operand evaluation other than a direct write target) is
+    /// confined to.
+    ///
+    /// Only validated (it may not exceed `core_size`) and carried along:
+    /// [`crate::emulators::generic_emulator::Emulator`] does not apply it,
+    /// so its reads are unrestricted whatever this is set to.
+    /// <https://corewar.co.uk/standards/icws94.htm#5.5.3>
+    pub read_limit: u64,
+
+    /// The width of the window, centered on the program counter, that a
+    /// write is confined to.
+    ///
+    /// Only validated (it may not exceed `core_size`) and carried along:
+    /// [`crate::emulators::generic_emulator::Emulator`] does not apply it,
+    /// so its writes are unrestricted whatever this is set to.
+    /// <https://corewar.co.uk/standards/icws94.htm#5.5.3>
+    pub write_limit: u64,
+
     /// A string identifying the format and version used by
     /// [`BytecodeInstructionIdentifier`]s.
     ///
@@ -255,6 +780,66 @@ pub struct CoreSettings {
     /// when not following this convention, implementations should be careful
     /// to change this string whenever the encoding changes.
     pub bytecode_format: Option<&'static str>,
+
+    /// The CoreWar rule set warriors loaded into this core are expected to
+    /// follow.
+    ///
+    /// This is descriptive metadata for a MARS to key its warrior
+    /// loading/validation off of; it doesn't by itself restrict which
+    /// instructions an emulator will execute once they're in the core.  See
+    /// [`crate::emulators::generic_emulator::standard_decoder`] for a decoder
+    /// that actually rejects bytecode outside a [`Standard`].
+    pub standard: Standard,
+}
+
+impl CoreSettings {
+    /// Validates and builds a [`CoreSettings`], so a caller with a candidate
+    /// configuration (e.g. one loaded from a tournament's hill definition)
+    /// can check it before handing it to an [`EmulatorCore`] implementation,
+    /// which may allocate a core as large as `core_size` up front.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`EmulatorError::InvalidParam`] if `core_size` doesn't fit
+    /// in a [`CoreAddr`], or if `pspace_size`, `read_limit`, or `write_limit`
+    /// is larger than `core_size`.
+    pub fn new(
+        core_size: u64,
+        pspace_size: u64,
+        warriors: u64,
+        processes: u64,
+        read_limit: u64,
+        write_limit: u64,
+    ) -> EmulatorResult<Self> {
+        if core_size > u64::from(CoreAddr::MAX) {
+            Err(EmulatorError::InvalidParam("core_size is too large"))
+        } else if pspace_size > core_size {
+            Err(EmulatorError::InvalidParam("pspace_size is too large"))
+        } else if read_limit > core_size {
+            Err(EmulatorError::InvalidParam("read_limit is too large"))
+        } else if write_limit > core_size {
+            Err(EmulatorError::InvalidParam("write_limit is too large"))
+        } else {
+            Ok(Self {
+                core_size,
+                pspace_size,
+                warriors,
+                processes,
+                read_limit,
+                write_limit,
+                bytecode_format: None,
+                standard: Standard::PMarsExtended,
+            })
+        }
+    }
+
+    /// Overrides [`Self::standard`], which [`Self::new`] otherwise defaults
+    /// to [`Standard::PMarsExtended`].
+    #[must_use]
+    pub const fn with_standard(mut self, standard: Standard) -> Self {
+        self.standard = standard;
+        self
+    }
 }
 
 /// Possible error kinds for operations on emulator implementations
@@ -1,19 +1,38 @@
 /// Encoding and decoding methods for in-core representation
 mod bytecode;
+/// Per-cell cache of opcode-specialized dispatch handlers, avoiding a fresh
+/// match on every cycle for cells whose instruction hasn't changed
+mod compiled;
 /// Core emulator instruction dispatch loop and [`crate::EmulatorCore`] trait
 /// implementation
 mod dispatch;
+/// A panic-free single-step harness over raw bytecode, for fuzzing and
+/// differential testing
+mod fuzz;
 /// Logic for executing decoded instructions in the emulator core
 mod emulation_operations;
+/// Backend-IR opcode classification, the first step toward a native code
+/// generator; see [`jit::IrOp`] for why it stops there for now
+mod jit;
 /// Logic for evaluating instruction operands including predecrement and
 /// postincrement core mutations
 mod operands;
+/// A write-journaled wrapper around in-core instruction storage, used to
+/// implement [`crate::EmulatorCore::snapshot`]/[`crate::EmulatorCore::rollback`]
+mod journal;
 /// A FIFO queue with configurable maximum size
 mod processes;
 /// Structures to track warrior pin assignments and pspace memory values
 mod pspace;
+/// Byte layout for [`crate::EmulatorCore::serialize_state`]/
+/// [`crate::EmulatorCore::deserialize_state`]
+mod state_blob;
 
+pub use bytecode::{
+    decode_region, standard_decoder, StandardDecoder, TableDecoder,
+};
 pub use dispatch::Emulator;
+pub use fuzz::{try_step_from_raw, StepOutcome};
 
 use crate::{
     emulator_core::{EmulatorError, EmulatorResult},
@@ -1,5 +1,7 @@
+use alloc::vec::Vec;
+
 use num_traits::cast::{FromPrimitive, ToPrimitive};
-use redcode;
+use redcode::{self, Standard};
 
 /// Translates from a redcode instruction to the encoded bytecode representation
 #[allow(
@@ -33,6 +35,134 @@ pub fn decode(bytecode: u32) -> Option<redcode::Instruction> {
     })
 }
 
+/// A [`decode`] that also rejects bytecode for a well-formed instruction
+/// using an opcode or addressing mode outside a chosen [`Standard`].
+///
+/// Built with [`standard_decoder`] rather than constructed directly, so the
+/// active opcode/modifier/addressing-mode sets stay derived from a single
+/// [`Standard`] rather than risking drifting out of sync with it.
+pub struct StandardDecoder {
+    /// The rule set bytecode is validated against
+    standard: Standard,
+}
+
+impl StandardDecoder {
+    /// Opcodes this decoder accepts.
+    #[must_use]
+    pub fn opcodes(&self) -> Vec<redcode::Opcode> {
+        self.standard.opcodes()
+    }
+
+    /// Addressing modes this decoder accepts for either field.
+    #[must_use]
+    pub fn addr_modes(&self) -> Vec<redcode::AddrMode> {
+        self.standard.addr_modes()
+    }
+
+    /// Decodes `bytecode`, returning `None` if it's malformed or if it
+    /// decodes to an instruction outside this decoder's [`Standard`].
+    #[must_use]
+    pub fn decode(&self, bytecode: u32) -> Option<redcode::Instruction> {
+        decode(bytecode).filter(|&instr| self.standard.allows(instr))
+    }
+}
+
+/// Builds a [`StandardDecoder`] that only accepts bytecode valid under
+/// `standard`, e.g. rejecting a `SEQ` or a predecrement-A operand under
+/// [`Standard::Icws88`].
+#[must_use]
+pub const fn standard_decoder(standard: Standard) -> StandardDecoder {
+    StandardDecoder { standard }
+}
+
+/// Number of distinct values a single bytecode byte can hold.
+const BYTE_VALUES: usize = 256;
+
+/// A batch decoder backed by precomputed per-byte lookup tables, for
+/// scanning large core regions (e.g. an evolver screening candidate cores)
+/// faster than calling [`decode`] once per instruction.
+///
+/// Where [`decode`] resolves each of an instruction's four bytes with a
+/// `from_u8` range check, [`Self::decode_region`] resolves each byte with a
+/// single array index into a table built once at construction. The tables
+/// double as the validity check: an invalid byte simply has `None` at its
+/// index, so there's no separate bounds check on the fast path.
+///
+/// Restricting to a non-default [`Standard`] is "free" here, since it's
+/// baked into the tables at construction instead of checked per instruction
+/// the way [`StandardDecoder`] checks it.
+pub struct TableDecoder {
+    /// `opcodes[b]` is the opcode byte `b` decodes to, if any
+    opcodes: [Option<redcode::Opcode>; BYTE_VALUES],
+    /// `modifiers[b]` is the modifier byte `b` decodes to, if any
+    modifiers: [Option<redcode::Modifier>; BYTE_VALUES],
+    /// `addr_modes[b]` is the addressing mode byte `b` decodes to, if any;
+    /// shared between the A-field and B-field bytes, which use the same
+    /// valid set
+    addr_modes: [Option<redcode::AddrMode>; BYTE_VALUES],
+}
+
+impl TableDecoder {
+    /// Builds the lookup tables for `standard`, once, up front.
+    #[must_use]
+    pub fn new(standard: Standard) -> Self {
+        let mut opcodes = [None; BYTE_VALUES];
+        let mut modifiers = [None; BYTE_VALUES];
+        let mut addr_modes = [None; BYTE_VALUES];
+        for byte in 0..=u8::MAX {
+            if let Some(slot) = opcodes.get_mut(usize::from(byte)) {
+                *slot = redcode::Opcode::from_u8(byte)
+                    .filter(|&op| standard.allows_opcode(op));
+            }
+            if let Some(slot) = modifiers.get_mut(usize::from(byte)) {
+                *slot = redcode::Modifier::from_u8(byte);
+            }
+            if let Some(slot) = addr_modes.get_mut(usize::from(byte)) {
+                *slot = redcode::AddrMode::from_u8(byte)
+                    .filter(|&mode| standard.allows_addr_mode(mode));
+            }
+        }
+        Self { opcodes, modifiers, addr_modes }
+    }
+
+    /// Decodes a single bytecode value via this decoder's tables.
+    #[must_use]
+    pub fn decode_one(&self, bytecode: u32) -> Option<redcode::Instruction> {
+        let [op, modifier, a_mode, b_mode] = u32::to_be_bytes(bytecode);
+        Some(redcode::Instruction {
+            opcode: (*self.opcodes.get(usize::from(op))?)?,
+            modifier: (*self.modifiers.get(usize::from(modifier))?)?,
+            a_addr_mode: (*self.addr_modes.get(usize::from(a_mode))?)?,
+            b_addr_mode: (*self.addr_modes.get(usize::from(b_mode))?)?,
+        })
+    }
+
+    /// Decodes `bytecode` into `out`, one slot per instruction, with no heap
+    /// allocation. Decodes `min(bytecode.len(), out.len())` slots; any extra
+    /// `out` slots are left untouched.
+    pub fn decode_region(
+        &self,
+        bytecode: &[u32],
+        out: &mut [Option<redcode::Instruction>],
+    ) {
+        for (&word, slot) in bytecode.iter().zip(out.iter_mut()) {
+            *slot = self.decode_one(word);
+        }
+    }
+}
+
+/// Convenience wrapper around [`TableDecoder`] for a one-off batch decode
+/// under [`Standard::PMarsExtended`] (the same unrestricted acceptance as
+/// [`decode`]). Building the tables costs three `256`-entry passes, trivial
+/// next to scanning a core of any realistic size; a caller doing this
+/// repeatedly should build and reuse a [`TableDecoder`] instead.
+pub fn decode_region(
+    bytecode: &[u32],
+    out: &mut [Option<redcode::Instruction>],
+) {
+    TableDecoder::new(Standard::PMarsExtended).decode_region(bytecode, out);
+}
+
 #[cfg(test)]
 #[cfg_attr(coverage, coverage(off))]
 mod tests {
@@ -133,4 +263,64 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn table_decoder_agrees_with_decode_for_all_instructions() {
+        let table = TableDecoder::new(Standard::PMarsExtended);
+        for instr in all_instructions() {
+            let bytecode = encode(instr);
+            assert_eq!(table.decode_one(bytecode), decode(bytecode));
+        }
+    }
+
+    #[test]
+    fn table_decoder_agrees_with_decode_over_random_bytecode() {
+        let table = TableDecoder::new(Standard::PMarsExtended);
+        let mut rng = rand::thread_rng();
+        for _ in 0..100_000 {
+            let bytecode: u32 = rng.gen();
+            assert_eq!(table.decode_one(bytecode), decode(bytecode));
+        }
+    }
+
+    #[test]
+    fn table_decoder_honors_standard_restrictions() {
+        let instr = Instruction {
+            opcode: redcode::Opcode::Seq,
+            modifier: redcode::Modifier::F,
+            a_addr_mode: redcode::AddrMode::Direct,
+            b_addr_mode: redcode::AddrMode::Direct,
+        };
+        let bytecode = encode(instr);
+
+        let icws88 = TableDecoder::new(Standard::Icws88);
+        assert_eq!(icws88.decode_one(bytecode), None);
+
+        let icws94 = TableDecoder::new(Standard::Icws94Draft);
+        assert_eq!(icws94.decode_one(bytecode), Some(instr));
+    }
+
+    #[test]
+    fn decode_region_fills_one_slot_per_instruction() {
+        let instrs: Vec<Instruction> = all_instructions().take(16).collect();
+        let bytecode: Vec<u32> = instrs.iter().copied().map(encode).collect();
+        let mut out = vec![None; bytecode.len()];
+
+        decode_region(&bytecode, &mut out);
+
+        let decoded: Vec<Instruction> =
+            out.into_iter().map(|instr| instr.unwrap()).collect();
+        assert_eq!(decoded, instrs);
+    }
+
+    #[test]
+    fn decode_region_stops_at_the_shorter_of_input_or_output() {
+        let instrs: Vec<Instruction> = all_instructions().take(4).collect();
+        let bytecode: Vec<u32> = instrs.iter().copied().map(encode).collect();
+        let mut out = vec![None; 2];
+
+        decode_region(&bytecode, &mut out);
+
+        assert_eq!(out, vec![Some(instrs[0]), Some(instrs[1])]);
+    }
 }
@@ -1,19 +1,22 @@
+use alloc::boxed::Box;
+
 use itertools::Itertools;
 use redcode;
 
 use super::{
     bytecode,
-    emulation_operations::{
-        arithmetic_op, cmp_op, dat_op, djn_op, jmn_op, jmp_op, jmz_op, ldp_op,
-        mov_op, nop_op, slt_op, sne_op, spl_op, stp_op, OpInputs,
-    },
+    compiled::CompiledTable,
+    emulation_operations::OpInputs,
+    journal::JournaledCore,
     operands,
     processes::ProcessQueueSet,
     pspace,
+    state_blob,
 };
 use crate::{
     emulator_core::{
-        CoreSettings, EmulatorCore, EmulatorError, EmulatorResult,
+        CoreObserver, CoreSettings, EmulatorCore, EmulatorError,
+        EmulatorResult, SnapshotToken,
     },
     BytecodeInstructionIdentifier, CoreAddr,
 };
@@ -24,6 +27,13 @@ pub struct Emulator {
     state: EmulatorState,
     /// Active settings applied to this emulator
     config: CoreSettings,
+    /// Ids of currently active snapshots, outermost first, in the same order
+    /// they were pushed onto `state`'s per-subsystem journals
+    snapshot_ids: Vec<u64>,
+    /// The id to hand out to the next [`EmulatorCore::snapshot`] call
+    next_snapshot_id: u64,
+    /// Observer attached with [`EmulatorCore::attach_observer`], if any
+    observer: Option<Box<dyn CoreObserver>>,
 }
 
 /// Mutable state of the current emulator and core memory
@@ -32,9 +42,12 @@ struct EmulatorState {
     /// from 0 to [`CoreSettings`]'s `warriors - 1`
     pq: ProcessQueueSet,
     /// Instruction and field values currently stored in the core
-    core: Vec<redcode::CompleteInstruction>,
+    core: JournaledCore,
     /// Pspace state for warriors that have been assigned PINs
     pspace: pspace::PSpace,
+    /// Cache of opcode-specialized dispatch handlers, one slot per core
+    /// address
+    compiled: CompiledTable,
 }
 
 impl Emulator {
@@ -49,34 +62,77 @@ impl Emulator {
         pspace_size: u64,
         warriors: u64,
         processes: u64,
+        read_limit: u64,
+        write_limit: u64,
     ) -> EmulatorResult<Self> {
-        if core_size > u64::from(CoreAddr::MAX) {
-            Err(EmulatorError::InvalidParam("core_size is too large"))
-        } else if pspace_size > core_size {
-            Err(EmulatorError::InvalidParam("pspace_size is too large"))
-        } else {
-            Ok(())
-        }?;
-        let config = CoreSettings {
+        let config = CoreSettings::new(
             core_size,
             pspace_size,
             warriors,
             processes,
-            bytecode_format: None,
-        };
+            read_limit,
+            write_limit,
+        )?;
+        let core_len = usize::try_from(core_size).map_err(|_err| {
+            EmulatorError::InternalError("impossibly large core_size")
+        })?;
         let state = EmulatorState {
             pq: ProcessQueueSet::new(&config),
-            core: vec![
+            core: JournaledCore::new(vec![
                 redcode::CompleteInstruction::default();
-                usize::try_from(core_size).map_err(|_err| {
-                    EmulatorError::InternalError("impossibly large core_size")
-                })?
-            ],
+                core_len
+            ]),
             pspace: pspace::PSpace::new(
                 pspace_size.try_into().unwrap_or_default(),
             ),
+            compiled: CompiledTable::new(core_len),
         };
-        Ok(Self { state, config })
+        Ok(Self {
+            state,
+            config,
+            snapshot_ids: Vec::new(),
+            next_snapshot_id: 0,
+            observer: None,
+        })
+    }
+
+    /// Convenience wrapper around [`Self::new`] that defaults `pspace_size`
+    /// to `core_size / 16`, the conventional ratio pMARS and other MARSs use
+    /// when the caller has no more specific pspace size in mind.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`EmulatorError::InvalidParam`] under the same conditions
+    /// as [`Self::new`].
+    pub fn new_with_default_pspace_size(
+        core_size: u64,
+        warriors: u64,
+        processes: u64,
+        read_limit: u64,
+        write_limit: u64,
+    ) -> EmulatorResult<Self> {
+        Self::new(
+            core_size,
+            core_size.checked_div(16).unwrap_or_default(),
+            warriors,
+            processes,
+            read_limit,
+            write_limit,
+        )
+    }
+
+    /// Overrides this emulator's `bytecode_format`, which [`Self::new`]
+    /// otherwise leaves as `None`.
+    ///
+    /// Without this, a blob this emulator saves with
+    /// [`EmulatorCore::serialize_state`] can never be restored, not even
+    /// onto itself: [`EmulatorCore::deserialize_state`] rejects any blob
+    /// without a non-empty `bytecode_format`, since its raw bytecode
+    /// identifiers aren't otherwise guaranteed portable.
+    #[must_use]
+    pub fn with_bytecode_format(mut self, format: &'static str) -> Self {
+        self.config.bytecode_format = Some(format);
+        self
     }
 
     /// Removes any existing pspace state, and writes a configuration based on
@@ -96,9 +152,9 @@ impl Emulator {
             self.validate_warrior_param(w, "invalid warrior ID in pspace map")?;
         }
         // Write an empty pspace config
-        self.state.pspace = pspace::PSpace::new(
-            self.config.pspace_size.try_into().unwrap_or_default(),
-        );
+        self.state
+            .pspace
+            .replace_all(self.config.pspace_size.try_into().unwrap_or_default());
         // Create a space for each pin
         for pin in pins.iter().unique() {
             self.state.pspace.add_pspace(*pin)?;
@@ -110,6 +166,51 @@ impl Emulator {
         Ok(())
     }
 
+    /// Convenience wrapper around [`Self::initialize_pspace`] for warriors
+    /// that only sometimes declare an explicit PIN (e.g. via a loadfile's
+    /// `PIN` pseudo-op).
+    ///
+    /// `explicit_pins` has one entry per warrior, in warrior ID order: `Some`
+    /// shares that warrior's PSPACE with every other warrior declaring the
+    /// same PIN, and `None` gives the warrior a private PSPACE, keyed by the
+    /// smallest PIN that no entry declares explicitly and no earlier `None`
+    /// entry was given, so it can't collide with another warrior's PIN.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`EmulatorError::InvalidParam`] if `explicit_pins` is
+    /// longer than [`CoreSettings::warriors`], or if
+    /// [`Self::initialize_pspace`] would.
+    pub fn initialize_pspace_with_defaults(
+        &mut self,
+        explicit_pins: &[Option<u64>],
+    ) -> EmulatorResult<()> {
+        let explicit: Vec<u64> =
+            explicit_pins.iter().flatten().copied().collect();
+        let mut next_private: u64 = 0;
+        let pspace_map = explicit_pins
+            .iter()
+            .enumerate()
+            .map(|(warrior_id, pin)| {
+                let warrior_id = u64::try_from(warrior_id).map_err(|_err| {
+                    EmulatorError::InternalError(
+                        "impossibly large warrior count",
+                    )
+                })?;
+                let pin = pin.unwrap_or_else(|| {
+                    while explicit.contains(&next_private) {
+                        next_private = next_private.wrapping_add(1);
+                    }
+                    let private = next_private;
+                    next_private = next_private.wrapping_add(1);
+                    private
+                });
+                Ok((pin, warrior_id))
+            })
+            .collect::<EmulatorResult<Vec<_>>>()?;
+        self.initialize_pspace(&pspace_map)
+    }
+
     /// executes a single instruction at pc as this warrior
     ///
     /// # Errors
@@ -124,6 +225,29 @@ impl Emulator {
         // Evaluate A and B operands
         // Cache the indexes and values at PC, a_target, b_target
         let regs = operands::evaluate(pc, &mut self.state.core)?;
+        if let Some(obs) = &mut self.observer {
+            obs.on_step(warrior_id, pc, regs.current.instr);
+            // The instruction itself and both its operand targets were just
+            // read out of the core to build `regs`.
+            obs.on_read(warrior_id, regs.current.idx);
+            obs.on_read(warrior_id, regs.a.idx);
+            obs.on_read(warrior_id, regs.b.idx);
+        }
+        // Decode-once: resolve (or reuse a cached resolution of) the
+        // specialized handler for the cell at PC, rather than re-matching on
+        // its opcode every cycle.
+        let pc_idx = usize::try_from(regs.current.idx).map_err(|_err| {
+            EmulatorError::InternalError("impossibly large core address")
+        })?;
+        let version = self.state.core.cell_version(pc_idx).ok_or(
+            EmulatorError::InternalError("pc larger than core size"),
+        )?;
+        let handler = self.state.compiled.handler(
+            pc_idx,
+            regs.current.instr.opcode,
+            version,
+        );
+
         let inputs = OpInputs {
             warrior_id,
             regs: &regs,
@@ -134,29 +258,11 @@ impl Emulator {
             pq: &mut self.state.pq,
             core: &mut self.state.core,
             pspace: &mut self.state.pspace,
+            observer: self.observer.as_deref_mut(),
         };
 
         // Execute the instruction at PC
-        match regs.current.instr.opcode {
-            redcode::Opcode::Dat => dat_op(inputs),
-            redcode::Opcode::Mov => mov_op(inputs),
-            redcode::Opcode::Add
-            | redcode::Opcode::Sub
-            | redcode::Opcode::Mul
-            | redcode::Opcode::Div
-            | redcode::Opcode::Mod => arithmetic_op(inputs),
-            redcode::Opcode::Jmp => jmp_op(inputs),
-            redcode::Opcode::Jmz => jmz_op(inputs),
-            redcode::Opcode::Jmn => jmn_op(inputs),
-            redcode::Opcode::Djn => djn_op(inputs),
-            redcode::Opcode::Spl => spl_op(inputs),
-            redcode::Opcode::Slt => slt_op(inputs),
-            redcode::Opcode::Cmp | redcode::Opcode::Seq => cmp_op(inputs),
-            redcode::Opcode::Sne => sne_op(inputs),
-            redcode::Opcode::Nop => nop_op(inputs),
-            redcode::Opcode::Ldp => ldp_op(inputs),
-            redcode::Opcode::Stp => stp_op(inputs),
-        }
+        handler(inputs)
     }
 
     /// Checks that a core address or value parameter is valid for this core
@@ -205,6 +311,11 @@ impl EmulatorCore for Emulator {
             // This warrior has an active process
             Some(pc) => {
                 self.step_emulator(pc, warrior_id)?;
+                if self.state.pq.queue_is_empty(warrior_id)? {
+                    if let Some(obs) = &mut self.observer {
+                        obs.on_death(warrior_id);
+                    }
+                }
                 Some(pc)
             }
             // This warrior has no active processes
@@ -218,13 +329,20 @@ impl EmulatorCore for Emulator {
         warriors_remaining: u64,
     ) -> EmulatorResult<u64> {
         let mut cycles_executed = 0;
+        // Reused across iterations so a long-running battle allocates once
+        // instead of twice per cycle.
+        let mut active = Vec::new();
         // This has the effect that two warriors may kill each other during one
         // cycle resulting in a tie for both
         // TODO(jespy) compare this behavior w/ pmars
-        while cycles_executed < cycles
-            && self.active_warrior_set().len() as u64 > warriors_remaining
-        {
-            for w in self.active_warrior_set() {
+        loop {
+            self.active_warriors_into(&mut active);
+            if cycles_executed >= cycles
+                || active.len() as u64 <= warriors_remaining
+            {
+                break;
+            }
+            for w in active.iter().copied() {
                 self.step(w)?;
             }
             cycles_executed = cycles_executed.saturating_add_signed(1);
@@ -342,12 +460,25 @@ impl EmulatorCore for Emulator {
 
     /// Removes any state associated with the core.  Writes the new values to
     /// the entire core.  All observable state is removed including process
-    /// queues, partial-cycle state, and all pspace mapping and values.  
+    /// queues, partial-cycle state, and all pspace mapping and values.
     fn reset_core(
         &mut self,
         initial_instr: BytecodeInstructionIdentifier,
         initial_a: CoreAddr,
         initial_b: CoreAddr,
+    ) -> EmulatorResult<()> {
+        self.reset_core_keep_pspace(initial_instr, initial_a, initial_b)?;
+        self.state.pspace.replace_all(
+            CoreAddr::try_from(self.config.pspace_size).unwrap_or_default(),
+        );
+        Ok(())
+    }
+
+    fn reset_core_keep_pspace(
+        &mut self,
+        initial_instr: BytecodeInstructionIdentifier,
+        initial_a: CoreAddr,
+        initial_b: CoreAddr,
     ) -> EmulatorResult<()> {
         let initial_complete_instr = redcode::CompleteInstruction {
             instr: self.bytecode_to_rc(initial_instr)?,
@@ -355,31 +486,29 @@ impl EmulatorCore for Emulator {
             b_field: initial_b,
         };
         self.state.pq.reset_queues();
-        self.state.core =
-            vec![
-                initial_complete_instr;
-                usize::try_from(self.config.core_size).map_err(|_err| {
-                    EmulatorError::InternalError("impossibly large core_size")
-                })?
-            ];
-        self.state.pspace = pspace::PSpace::new(
-            CoreAddr::try_from(self.config.pspace_size).unwrap_or_default(),
-        );
+        self.state.core.replace_all(vec![
+            initial_complete_instr;
+            usize::try_from(self.config.core_size).map_err(|_err| {
+                EmulatorError::InternalError("impossibly large core_size")
+            })?
+        ]);
         Ok(())
     }
 
-    /// Returns the set of warriors with non-empty process queues.
-    fn active_warrior_set(&self) -> Vec<u64> {
-        self.state.pq.active_warriors()
+    /// Writes the set of warriors with non-empty process queues into `out`.
+    fn active_warriors_into(&self, out: &mut Vec<u64>) -> usize {
+        self.state.pq.active_warriors_into(out)
     }
 
-    /// Returns a copy of the process queue for a warrior.  This will be empty
-    /// for inactive warriors.  Otherwise the next process to execute is first.
-    fn read_process_queue(
+    /// Writes the process queue for a warrior into `out`.  This will write
+    /// nothing for inactive warriors.  Otherwise the next process to execute
+    /// is first.
+    fn process_queue_into(
         &self,
         warrior_id: u64,
-    ) -> EmulatorResult<Vec<CoreAddr>> {
-        self.state.pq.read_queue(warrior_id)
+        out: &mut Vec<CoreAddr>,
+    ) -> EmulatorResult<usize> {
+        self.state.pq.read_queue_into(warrior_id, out)
     }
 
     /// Replaces the warriors current processes with the values in the input.
@@ -417,4 +546,240 @@ impl EmulatorCore for Emulator {
     ) -> BytecodeInstructionIdentifier {
         bytecode::encode(redcode_instr)
     }
+
+    /// Captures all observable state by starting a new undo frame in each of
+    /// `core`, `pq`, and `pspace`.
+    fn snapshot(&mut self) -> EmulatorResult<SnapshotToken> {
+        // One counter for every emulator in the process, so a token taken
+        // from another emulator never matches an id active on this one.
+        static NEXT_SNAPSHOT_ID: core::sync::atomic::AtomicU64 =
+            core::sync::atomic::AtomicU64::new(0);
+        let id = NEXT_SNAPSHOT_ID
+            .fetch_update(
+                core::sync::atomic::Ordering::Relaxed,
+                core::sync::atomic::Ordering::Relaxed,
+                |id| id.checked_add(1),
+            )
+            .map_err(|_err| {
+                EmulatorError::InternalError("snapshot id counter overflowed")
+            })?;
+        self.snapshot_ids.push(id);
+        self.state.core.push_snapshot_frame();
+        self.state.pq.push_snapshot_frame();
+        self.state.pspace.push_snapshot_frame();
+        Ok(SnapshotToken(id))
+    }
+
+    /// Replays and discards undo frames from `core`, `pq`, and `pspace`, down
+    /// to and including the one matching `token`.
+    fn rollback(&mut self, token: SnapshotToken) -> EmulatorResult<()> {
+        let Some(pos) =
+            self.snapshot_ids.iter().position(|&id| id == token.0)
+        else {
+            return Err(EmulatorError::InvalidParam(
+                "snapshot token is stale, already rolled back, or from a \
+                 different emulator",
+            ));
+        };
+        while self.snapshot_ids.len() > pos {
+            self.snapshot_ids.pop();
+            self.state.core.pop_snapshot_frame();
+            self.state.pq.pop_snapshot_frame();
+            self.state.pspace.pop_snapshot_frame();
+        }
+        Ok(())
+    }
+
+    fn attach_observer(&mut self, obs: Box<dyn CoreObserver>) {
+        self.observer = Some(obs);
+    }
+
+    fn detach_observer(&mut self) {
+        self.observer = None;
+    }
+
+    /// Captures core cells, process queues, and pspace into a
+    /// [`state_blob`]-encoded blob.
+    fn serialize_state(&self) -> EmulatorResult<Vec<u8>> {
+        let core = self
+            .state
+            .core
+            .iter()
+            .map(|cell| {
+                (self.rc_to_bytecode(cell.instr), cell.a_field, cell.b_field)
+            })
+            .collect::<Vec<_>>();
+        let mut queues = Vec::new();
+        for warrior_id in 0..self.config.warriors {
+            queues.push(self.state.pq.read_queue(warrior_id)?);
+        }
+        let pins = self
+            .state
+            .pspace
+            .pins()
+            .map(|(pin, buf)| (pin, buf.to_vec()))
+            .collect::<Vec<_>>();
+        let warrior_pins = self.state.pspace.warrior_pins().collect::<Vec<_>>();
+        let zero_values = self.state.pspace.zero_values().collect::<Vec<_>>();
+        state_blob::encode(
+            &self.config,
+            &core,
+            &queues,
+            &pins,
+            &warrior_pins,
+            &zero_values,
+        )
+    }
+
+    /// Parses a [`state_blob`]-encoded blob, validating it against this
+    /// emulator's settings, then replaces core cells, process queues, and
+    /// pspace with its contents.
+    ///
+    /// Replacing the core cells goes through [`JournaledCore::replace_all`],
+    /// which bumps its epoch; every entry in [`CompiledTable`] was resolved
+    /// against an older epoch, so the next [`Emulator::step_emulator`] of any
+    /// cell re-resolves its handler from the restored opcode rather than
+    /// reusing one cached from before the restore.
+    fn deserialize_state(&mut self, bytes: &[u8]) -> EmulatorResult<()> {
+        let decoded = state_blob::decode(bytes, &self.config)?;
+        let mut cells = Vec::with_capacity(decoded.core.len());
+        for (bytecode, a_field, b_field) in decoded.core {
+            cells.push(redcode::CompleteInstruction {
+                instr: self.bytecode_to_rc(bytecode)?,
+                a_field,
+                b_field,
+            });
+        }
+        self.state.core.replace_all(cells);
+        for (warrior_idx, queue) in decoded.queues.into_iter().enumerate() {
+            let warrior_id = u64::try_from(warrior_idx).map_or(
+                Err(EmulatorError::InternalError(
+                    "impossibly large warrior index",
+                )),
+                Ok,
+            )?;
+            self.state.pq.replace_queue(warrior_id, &queue)?;
+        }
+        self.state.pspace.restore(
+            decoded.pspace_size,
+            decoded.warrior_to_pin,
+            decoded.zero_index_values,
+            decoded.pin_to_pspace,
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod tests {
+    use redcode::{AddrMode, Instruction, Modifier, Opcode};
+
+    use super::*;
+
+    /// An emulator with a `bytecode_format` set, so its serialized state is
+    /// eligible to be restored at all; see [`Emulator::with_bytecode_format`].
+    fn sample_emulator(format: &'static str) -> Emulator {
+        Emulator::new(8, 4, 2, 4, 8, 8)
+            .expect("valid core settings")
+            .with_bytecode_format(format)
+    }
+
+    #[test]
+    fn serialize_state_roundtrips_core_queues_and_pspace() {
+        let mut emulator = sample_emulator("test::dispatch::roundtrip::1");
+
+        let instr = Instruction {
+            opcode: Opcode::Mov,
+            modifier: Modifier::I,
+            a_addr_mode: AddrMode::Direct,
+            b_addr_mode: AddrMode::Direct,
+        };
+        let bytecode = emulator.rc_to_bytecode(instr);
+        let fields: [(CoreAddr, CoreAddr, CoreAddr); 8] = [
+            (0, 0, 1),
+            (1, 1, 2),
+            (2, 2, 3),
+            (3, 3, 4),
+            (4, 4, 5),
+            (5, 5, 6),
+            (6, 6, 7),
+            (7, 7, 0),
+        ];
+        for (addr, a_field, b_field) in fields {
+            emulator
+                .write_core(addr, bytecode, a_field, b_field)
+                .expect("valid core write");
+        }
+        emulator
+            .replace_process_queue(0, &[1, 3, 5])
+            .expect("valid process queue");
+        emulator
+            .replace_process_queue(1, &[2])
+            .expect("valid process queue");
+        emulator
+            .initialize_pspace(&[(10, 0), (10, 1)])
+            .expect("valid pspace map");
+        emulator.write_pspace(0, 1, 3).expect("valid pspace write");
+        emulator.write_pspace(1, 2, 5).expect("valid pspace write");
+
+        let blob = emulator.serialize_state().expect("serialize succeeds");
+
+        let mut restored = sample_emulator("test::dispatch::roundtrip::1");
+        restored
+            .deserialize_state(&blob)
+            .expect("deserialize succeeds");
+
+        for addr in 0..8 {
+            assert_eq!(
+                restored.read_core(addr).expect("valid core read"),
+                emulator.read_core(addr).expect("valid core read"),
+                "core cell {addr} should round-trip instruction-for-instruction"
+            );
+        }
+        for warrior_id in 0..2 {
+            let mut expected = Vec::new();
+            let mut actual = Vec::new();
+            emulator
+                .process_queue_into(warrior_id, &mut expected)
+                .expect("valid queue read");
+            restored
+                .process_queue_into(warrior_id, &mut actual)
+                .expect("valid queue read");
+            assert_eq!(actual, expected);
+        }
+        assert_eq!(restored.read_pspace(0, 1).expect("valid pspace read"), 3);
+        assert_eq!(restored.read_pspace(1, 2).expect("valid pspace read"), 5);
+    }
+
+    #[test]
+    fn deserialize_state_rejects_missing_bytecode_format() {
+        let emulator = Emulator::new(8, 4, 2, 4, 8, 8)
+            .expect("valid core settings");
+        let blob = emulator.serialize_state().expect("serialize succeeds");
+
+        let mut restored = Emulator::new(8, 4, 2, 4, 8, 8)
+            .expect("valid core settings");
+        assert!(restored.deserialize_state(&blob).is_err());
+    }
+
+    #[test]
+    fn deserialize_state_rejects_mismatched_bytecode_format() {
+        let emulator = sample_emulator("test::dispatch::format::a");
+        let blob = emulator.serialize_state().expect("serialize succeeds");
+
+        let mut restored = sample_emulator("test::dispatch::format::b");
+        assert!(restored.deserialize_state(&blob).is_err());
+    }
+
+    #[test]
+    fn deserialize_state_rejects_mismatched_core_size() {
+        let emulator = sample_emulator("test::dispatch::geometry");
+        let blob = emulator.serialize_state().expect("serialize succeeds");
+
+        let mut restored = Emulator::new(16, 4, 2, 4, 8, 8)
+            .expect("valid core settings")
+            .with_bytecode_format("test::dispatch::geometry");
+        assert!(restored.deserialize_state(&blob).is_err());
+    }
 }
@@ -1,10 +1,11 @@
 use redcode::{CompleteInstruction, Modifier, Opcode};
 
 use super::{
-    offset, operands::RegisterValues, processes::ProcessQueueSet, pspace,
+    journal::JournaledCore, offset, operands::RegisterValues,
+    processes::ProcessQueueSet, pspace,
 };
 use crate::{
-    emulator_core::{EmulatorError, EmulatorResult},
+    emulator_core::{CoreObserver, EmulatorError, EmulatorResult},
     CoreAddr,
 };
 
@@ -21,13 +22,24 @@ pub struct OpInputs<'a> {
     /// enqueue processes.
     pub pq: &'a mut ProcessQueueSet,
     /// Reference to in-core instructions.
-    pub core: &'a mut [CompleteInstruction],
+    pub core: &'a mut JournaledCore,
     /// PSPACE state shared by processes in the core.
     pub pspace: &'a mut pspace::PSpace,
+    /// Attached observer, if any, notified whenever an opcode writes to the
+    /// core.
+    ///
+    /// Bounded `+ 'static` rather than the `+ 'a` the elided form would give:
+    /// the observer comes from `Emulator`'s `Option<Box<dyn CoreObserver>>`,
+    /// which is itself `'static`-bound, and `&mut` is invariant in its
+    /// referent, so a reborrow can't narrow that bound down to `'a` at the
+    /// call site. Only the reference's own lifetime needs to shrink to `'a`,
+    /// which reborrowing already handles.
+    pub observer: Option<&'a mut (dyn CoreObserver + 'static)>,
 }
 
 impl<'a> OpInputs<'a> {
-    /// Gets a mutable reference to an in-core address
+    /// Gets a mutable reference to an in-core address, notifying the attached
+    /// observer (if any) that `warrior_id` wrote to `addr` first.
     ///
     /// This helper improves error handling and allows enabling clippy's
     /// `indexing_slicing` lint. However I'd like to rip it out.
@@ -41,12 +53,33 @@ impl<'a> OpInputs<'a> {
         self: OpInputs<'a>,
         addr: CoreAddr,
     ) -> EmulatorResult<&'a mut CompleteInstruction> {
+        if let Some(obs) = self.observer {
+            obs.on_write(self.warrior_id, addr);
+        }
         self.core
             .get_mut(addr as usize)
             .ok_or(EmulatorError::InternalError(
                 "attempt to write to invalid core index",
             ))
     }
+
+    /// Enqueues `addr` to run next for the currently executing warrior,
+    /// notifying the attached observer (if any) first.
+    ///
+    /// Unlike [`Self::core_get_mut`], this doesn't need to consume `self`:
+    /// it only needs `&mut` access to the `pq` and `observer` fields, not a
+    /// borrow that outlives the call.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `addr` or the currently executing warrior are
+    /// invalid
+    fn enqueue(&mut self, addr: CoreAddr) -> EmulatorResult<()> {
+        if let Some(obs) = self.observer.as_deref_mut() {
+            obs.on_enqueue(self.warrior_id, addr);
+        }
+        self.pq.push_back(addr, self.warrior_id)
+    }
 }
 
 /// Implementation of the [`Opcode::Dat`] instruction
@@ -59,9 +92,9 @@ pub fn dat_op(_inputs: OpInputs) -> EmulatorResult<()> {
 }
 
 /// Implementation of the [`Opcode::Mov`] instruction
-pub fn mov_op(inputs: OpInputs) -> EmulatorResult<()> {
+pub fn mov_op(mut inputs: OpInputs) -> EmulatorResult<()> {
     let next_pc = offset(inputs.regs.current.idx, 1, inputs.core_size)?;
-    inputs.pq.push_back(next_pc, inputs.warrior_id)?;
+    inputs.enqueue(next_pc)?;
     match inputs.regs.current.instr.modifier {
         Modifier::A => {
             // A MOV.A instruction would replace the A-number of the
@@ -186,11 +219,10 @@ fn perform_arithmetic(
 
 /// Implementation of the [`Opcode::Add`], [`Opcode::Sub`], [`Opcode::Mul`],
 /// [`Opcode::Div`], and [`Opcode::Mod`] instruction
-pub fn arithmetic_op(inputs: OpInputs) -> EmulatorResult<()> {
+pub fn arithmetic_op(mut inputs: OpInputs) -> EmulatorResult<()> {
     let a = inputs.regs.a;
     let b = inputs.regs.b;
     let next_pc = offset(inputs.regs.current.idx, 1, inputs.core_size)?;
-    let war_id = inputs.warrior_id;
     if inputs.core_size == 0 {
         return Err(EmulatorError::InternalError("Core Size cannot be zero"));
     }
@@ -204,7 +236,7 @@ pub fn arithmetic_op(inputs: OpInputs) -> EmulatorResult<()> {
             let a_value = a.a_field;
             let b_value = b.a_field;
             if let Some(res) = perform_arithmetic(b_value, a_value, &inputs) {
-                inputs.pq.push_back(next_pc, war_id)?;
+                inputs.enqueue(next_pc)?;
                 inputs.core_get_mut(b.idx)?.a_field = res?;
             };
         }
@@ -215,7 +247,7 @@ pub fn arithmetic_op(inputs: OpInputs) -> EmulatorResult<()> {
             let a_value = a.b_field;
             let b_value = b.b_field;
             if let Some(res) = perform_arithmetic(b_value, a_value, &inputs) {
-                inputs.pq.push_back(next_pc, war_id)?;
+                inputs.enqueue(next_pc)?;
                 inputs.core_get_mut(b.idx)?.b_field = res?;
             }
         }
@@ -227,7 +259,7 @@ pub fn arithmetic_op(inputs: OpInputs) -> EmulatorResult<()> {
             let a_value = a.a_field;
             let b_value = b.b_field;
             if let Some(res) = perform_arithmetic(b_value, a_value, &inputs) {
-                inputs.pq.push_back(next_pc, war_id)?;
+                inputs.enqueue(next_pc)?;
                 inputs.core_get_mut(b.idx)?.b_field = res?;
             }
         }
@@ -238,7 +270,7 @@ pub fn arithmetic_op(inputs: OpInputs) -> EmulatorResult<()> {
             let a_value = a.b_field;
             let b_value = b.a_field;
             if let Some(res) = perform_arithmetic(b_value, a_value, &inputs) {
-                inputs.pq.push_back(next_pc, war_id)?;
+                inputs.enqueue(next_pc)?;
                 inputs.core_get_mut(b.idx)?.a_field = res?;
             }
         }
@@ -256,7 +288,7 @@ pub fn arithmetic_op(inputs: OpInputs) -> EmulatorResult<()> {
             match (first_result, second_result) {
                 (Some(first), Some(second)) => {
                     // if there was no division by zero, continue as normal
-                    inputs.pq.push_back(next_pc, war_id)?;
+                    inputs.enqueue(next_pc)?;
                     let target = inputs.core_get_mut(b.idx)?;
                     target.a_field = first?;
                     target.b_field = second?;
@@ -293,7 +325,7 @@ pub fn arithmetic_op(inputs: OpInputs) -> EmulatorResult<()> {
             match (first_result, second_result) {
                 (Some(first), Some(second)) => {
                     // if there was no division by zero, continue as normal
-                    inputs.pq.push_back(next_pc, war_id)?;
+                    inputs.enqueue(next_pc)?;
                     let target = inputs.core_get_mut(b.idx)?;
                     target.b_field = first?;
                     target.a_field = second?;
@@ -321,14 +353,14 @@ pub fn arithmetic_op(inputs: OpInputs) -> EmulatorResult<()> {
 }
 
 /// Implementation of the [`Opcode::Jmp`] instruction
-pub fn jmp_op(inputs: OpInputs) -> EmulatorResult<()> {
+pub fn jmp_op(mut inputs: OpInputs) -> EmulatorResult<()> {
     // jmp unconditionally adds the b pointer to the process queue
-    inputs.pq.push_back(inputs.regs.a.idx, inputs.warrior_id)?;
+    inputs.enqueue(inputs.regs.a.idx)?;
     Ok(())
 }
 
 /// Implementation of the [`Opcode::Jmz`] instruction
-pub fn jmz_op(inputs: OpInputs) -> EmulatorResult<()> {
+pub fn jmz_op(mut inputs: OpInputs) -> EmulatorResult<()> {
     // JMZ tests the B-value to determine if it is zero.  If the B-value is
     // zero, the sum of the program counter and the A-pointer is queued.
     // Otherwise, the next instruction is queued (PC + 1).  JMZ.I functions
@@ -351,16 +383,16 @@ pub fn jmz_op(inputs: OpInputs) -> EmulatorResult<()> {
         }
     };
     if is_zero {
-        inputs.pq.push_back(a.idx, inputs.warrior_id)?;
+        inputs.enqueue(a.idx)?;
     } else {
         let next_pc = offset(inputs.regs.current.idx, 1, inputs.core_size)?;
-        inputs.pq.push_back(next_pc, inputs.warrior_id)?;
+        inputs.enqueue(next_pc)?;
     }
     Ok(())
 }
 
 /// Implementation of the [`Opcode::Jmn`] instruction
-pub fn jmn_op(inputs: OpInputs) -> EmulatorResult<()> {
+pub fn jmn_op(mut inputs: OpInputs) -> EmulatorResult<()> {
     // JMN tests the B-value to determine if it is zero.  If the B-value is not
     // zero, the sum of the program counter and the A-pointer is queued.
     // Otherwise, the next instruction is queued (PC + 1).  JMN.I functions as
@@ -384,16 +416,16 @@ pub fn jmn_op(inputs: OpInputs) -> EmulatorResult<()> {
         }
     };
     if is_non_zero {
-        inputs.pq.push_back(a.idx, inputs.warrior_id)?;
+        inputs.enqueue(a.idx)?;
     } else {
         let next_pc = offset(inputs.regs.current.idx, 1, inputs.core_size);
-        inputs.pq.push_back(next_pc?, inputs.warrior_id)?;
+        inputs.enqueue(next_pc?)?;
     }
     Ok(())
 }
 
 /// Implementation of the [`Opcode::Djn`] instruction
-pub fn djn_op(inputs: OpInputs) -> EmulatorResult<()> {
+pub fn djn_op(mut inputs: OpInputs) -> EmulatorResult<()> {
     // DJN decrements the B-value and the B-target, then tests the B-value to
     // determine if it is zero.  If the decremented B-value is not zero, the
     // sum of the program counter and the A-pointer is queued. Otherwise, the
@@ -407,6 +439,9 @@ pub fn djn_op(inputs: OpInputs) -> EmulatorResult<()> {
     let next_pc = offset(inputs.regs.current.idx, 1, inputs.core_size)?;
     let war_id = inputs.warrior_id;
     let modifier = inputs.regs.current.instr.modifier;
+    if let Some(obs) = inputs.observer.as_deref_mut() {
+        obs.on_write(war_id, b.idx);
+    }
     let Some(b_target) = inputs.core.get_mut(b.idx as usize) else {
         return Err(EmulatorError::InternalError(
             "attempt to write to invalid core index",
@@ -422,9 +457,9 @@ pub fn djn_op(inputs: OpInputs) -> EmulatorResult<()> {
             b_target.a_field = b_target_a;
             let non_zero = decrement(b.a_field)? != 0;
             if non_zero {
-                inputs.pq.push_back(a.idx, war_id)?;
+                inputs.enqueue(a.idx)?;
             } else {
-                inputs.pq.push_back(next_pc, war_id)?;
+                inputs.enqueue(next_pc)?;
             }
         }
         Modifier::B | Modifier::AB => {
@@ -435,9 +470,9 @@ pub fn djn_op(inputs: OpInputs) -> EmulatorResult<()> {
             b_target.b_field = b_target_b;
             let non_zero = decrement(b.b_field)? != 0;
             if non_zero {
-                inputs.pq.push_back(a.idx, war_id)?;
+                inputs.enqueue(a.idx)?;
             } else {
-                inputs.pq.push_back(next_pc, war_id)?;
+                inputs.enqueue(next_pc)?;
             }
         }
         Modifier::F | Modifier::X | Modifier::I => {
@@ -452,9 +487,9 @@ pub fn djn_op(inputs: OpInputs) -> EmulatorResult<()> {
             let non_zero =
                 decrement(b.a_field)? != 0 || decrement(b.b_field)? != 0;
             if non_zero {
-                inputs.pq.push_back(a.idx, war_id)?;
+                inputs.enqueue(a.idx)?;
             } else {
-                inputs.pq.push_back(next_pc, war_id)?;
+                inputs.enqueue(next_pc)?;
             }
         }
     };
@@ -462,18 +497,18 @@ pub fn djn_op(inputs: OpInputs) -> EmulatorResult<()> {
 }
 
 /// Implementation of the [`Opcode::Spl`] instruction
-pub fn spl_op(inputs: OpInputs) -> EmulatorResult<()> {
+pub fn spl_op(mut inputs: OpInputs) -> EmulatorResult<()> {
     // SPL queues the next instruction (PC + 1) and then queues the sum of the
     // program counter and A-pointer. If the queue is full, only the next
     // instruction is queued.
     let next_pc = offset(inputs.regs.current.idx, 1, inputs.core_size);
-    inputs.pq.push_back(next_pc?, inputs.warrior_id)?;
-    inputs.pq.push_back(inputs.regs.a.idx, inputs.warrior_id)?;
+    inputs.enqueue(next_pc?)?;
+    inputs.enqueue(inputs.regs.a.idx)?;
     Ok(())
 }
 
 /// Implementation of the [`Opcode::Slt`] instruction
-pub fn slt_op(inputs: OpInputs) -> EmulatorResult<()> {
+pub fn slt_op(mut inputs: OpInputs) -> EmulatorResult<()> {
     // SLT compares the A-value to the B-value.  If the A-value is less than
     // the B-value, the instruction after the next instruction (PC + 2) is
     // queued (skipping the next instruction).  Otherwise, the next
@@ -492,15 +527,12 @@ pub fn slt_op(inputs: OpInputs) -> EmulatorResult<()> {
     };
     // Increment PC twice if the condition holds, otherwise increment once
     let amt = if is_less_than { 2 } else { 1 };
-    inputs.pq.push_back(
-        offset(inputs.regs.current.idx, amt, inputs.core_size)?,
-        inputs.warrior_id,
-    )?;
+    inputs.enqueue(offset(inputs.regs.current.idx, amt, inputs.core_size)?)?;
     Ok(())
 }
 
 /// Implementation of the [`Opcode::Cmp`] and [`Opcode::Seq`] instructions
-pub fn cmp_op(inputs: OpInputs) -> EmulatorResult<()> {
+pub fn cmp_op(mut inputs: OpInputs) -> EmulatorResult<()> {
     // CMP compares the A-value to the B-value.  If the result of the
     // comparison is equal, the instruction after the next instruction
     // (PC + 2) is queued (skipping the next instruction).  Otherwise, the
@@ -522,15 +554,12 @@ pub fn cmp_op(inputs: OpInputs) -> EmulatorResult<()> {
     };
     // Increment PC twice if the condition holds, otherwise increment once
     let amt = if is_equal { 2 } else { 1 };
-    inputs.pq.push_back(
-        offset(inputs.regs.current.idx, amt, inputs.core_size)?,
-        inputs.warrior_id,
-    )?;
+    inputs.enqueue(offset(inputs.regs.current.idx, amt, inputs.core_size)?)?;
     Ok(())
 }
 
 /// Implementation of the [`Opcode::Sne`] instruction
-pub fn sne_op(inputs: OpInputs) -> EmulatorResult<()> {
+pub fn sne_op(mut inputs: OpInputs) -> EmulatorResult<()> {
     // SNE compares the A-value to the B-value.  If the result of the
     // comparison is not equal, the instruction after the next instruction
     // (PC + 2) is queued (skipping the next instruction).  Otherwise, the
@@ -552,26 +581,20 @@ pub fn sne_op(inputs: OpInputs) -> EmulatorResult<()> {
     };
     // Increment PC twice if the condition holds, otherwise increment once
     let amt = if is_not_equal { 2 } else { 1 };
-    inputs.pq.push_back(
-        offset(inputs.regs.current.idx, amt, inputs.core_size)?,
-        inputs.warrior_id,
-    )?;
+    inputs.enqueue(offset(inputs.regs.current.idx, amt, inputs.core_size)?)?;
     Ok(())
 }
 
 /// Implementation of the [`Opcode::Nop`] instruction
-pub fn nop_op(inputs: OpInputs) -> EmulatorResult<()> {
+pub fn nop_op(mut inputs: OpInputs) -> EmulatorResult<()> {
     // Increments and queues the PC but otherwise has no effect past operand
     // evaluation
-    inputs.pq.push_back(
-        offset(inputs.regs.current.idx, 1, inputs.core_size)?,
-        inputs.warrior_id,
-    )?;
+    inputs.enqueue(offset(inputs.regs.current.idx, 1, inputs.core_size)?)?;
     Ok(())
 }
 
 /// Implementation of the [`Opcode::Ldp`] instruction
-pub fn ldp_op(inputs: OpInputs) -> EmulatorResult<()> {
+pub fn ldp_op(mut inputs: OpInputs) -> EmulatorResult<()> {
     // Reads a value from the PSPACE, writing it into core memory
     //
     // LDP and STP are not defined in any ICWS standard.  This implementation
@@ -613,10 +636,7 @@ pub fn ldp_op(inputs: OpInputs) -> EmulatorResult<()> {
     // In general: (x % coresize) % pspace size != (x % pspace size) % coresize
     //
     // Queue PC + 1
-    inputs.pq.push_back(
-        offset(inputs.regs.current.idx, 1, inputs.core_size)?,
-        inputs.warrior_id,
-    )?;
+    inputs.enqueue(offset(inputs.regs.current.idx, 1, inputs.core_size)?)?;
     let a = inputs.regs.a;
     let b = inputs.regs.b;
     let source_index = match inputs.regs.current.instr.modifier {
@@ -646,7 +666,7 @@ pub fn ldp_op(inputs: OpInputs) -> EmulatorResult<()> {
 }
 
 /// Implementation of the [`Opcode::Stp`] instruction
-pub fn stp_op(inputs: OpInputs) -> EmulatorResult<()> {
+pub fn stp_op(mut inputs: OpInputs) -> EmulatorResult<()> {
     // Reads a value from the PSPACE, writing it into core memory
     //
     // LDP and STP are not defined in any ICWS standard.  This implementation
@@ -682,14 +702,17 @@ pub fn stp_op(inputs: OpInputs) -> EmulatorResult<()> {
         }
     };
 
+    // Index zero is not shared between warriors with the same pin: `write`
+    // already routes it to a private per-warrior slot instead of the shared
+    // pin buffer, so an STP targeting it is a normal write as far as this
+    // round is concerned. It just doesn't survive to the next round, because
+    // the battle driver overwrites it with the new round's result code
+    // before resuming; see [`PSpace::write`].
     inputs
         .pspace
         .write(pspace_dest_index, source_value, inputs.warrior_id)?;
 
     // Queue PC + 1
-    inputs.pq.push_back(
-        offset(inputs.regs.current.idx, 1, inputs.core_size)?,
-        inputs.warrior_id,
-    )?;
+    inputs.enqueue(offset(inputs.regs.current.idx, 1, inputs.core_size)?)?;
     Ok(())
 }
@@ -0,0 +1,87 @@
+use alloc::vec::Vec;
+
+use super::dispatch::Emulator;
+use crate::{
+    emulator_core::{EmulatorCore, EmulatorError, EmulatorResult},
+    trace::{RawCell, TraceRecorder},
+    CoreAddr,
+};
+
+/// The observable effect of a single [`try_step_from_raw`] call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StepOutcome {
+    /// Every core cell whose contents changed, paired with its new value.
+    pub mutated_cells: Vec<(CoreAddr, RawCell)>,
+    /// `warrior_id`'s process queue after the step; empty if it died.
+    pub process_queue: Vec<CoreAddr>,
+}
+
+/// Builds a throwaway, single-warrior [`Emulator`] out of `core_image`, then
+/// decodes and executes exactly one instruction for `warrior_id` at `pc`.
+///
+/// Every cell of `core_image` is validated through
+/// [`EmulatorCore::write_core`] - the same [`EmulatorCore::bytecode_to_rc`]
+/// decode the dispatch loop runs on every cell it executes - so arbitrary
+/// bytes, the kind a fuzzer or a differential test against a reference MARS
+/// would feed in, come back as an [`EmulatorError`] rather than reaching an
+/// internal `unwrap`.
+///
+/// `warrior_id` is given a default-sized private PSPACE (see
+/// [`Emulator::new_with_default_pspace_size`]), so `LDP`/`STP` behave rather
+/// than erroring for lack of any configured PSPACE. That size is
+/// `core_image.len() / 16`, so an image of fewer than 16 cells gets a
+/// zero-sized PSPACE, on which `LDP`/`STP` still return an error.
+///
+/// Reuses [`TraceRecorder::record_step`] for the before/after diff rather
+/// than reimplementing it, so a harness that wants the same
+/// `(address, before, after)` shape across a whole battle, not just one
+/// throwaway step, can swap to driving [`TraceRecorder`] directly against a
+/// long-lived [`Emulator`].
+///
+/// # Errors
+///
+/// Returns [`EmulatorError::InvalidParam`] if `core_image` is empty or
+/// larger than a [`CoreAddr`] can index, if any cell fails to decode, or if
+/// `pc` or `warrior_id` are out of range; other [`EmulatorError`]s as
+/// [`EmulatorCore::step`] would.
+pub fn try_step_from_raw(
+    core_image: &[RawCell],
+    pc: CoreAddr,
+    warrior_id: u64,
+) -> EmulatorResult<StepOutcome> {
+    let core_size = u64::try_from(core_image.len()).map_err(|_err| {
+        EmulatorError::InvalidParam("core_image is larger than a core can be")
+    })?;
+    if core_size == 0 {
+        return Err(EmulatorError::InvalidParam(
+            "core_image must not be empty",
+        ));
+    }
+    let warriors = warrior_id.checked_add(1).ok_or(
+        EmulatorError::InvalidParam("warrior_id is too large"),
+    )?;
+
+    let mut emulator = Emulator::new_with_default_pspace_size(
+        core_size, warriors, core_size, core_size, core_size,
+    )?;
+    emulator
+        .initialize_pspace_with_defaults(&vec![None; warriors as usize])?;
+    for (idx, &(instr, a_field, b_field)) in core_image.iter().enumerate() {
+        let addr = CoreAddr::try_from(idx).map_err(|_err| {
+            EmulatorError::InvalidParam(
+                "core_image is larger than a core can be",
+            )
+        })?;
+        emulator.write_core(addr, instr, a_field, b_field)?;
+    }
+    emulator.replace_process_queue(warrior_id, &[pc])?;
+
+    let mutated_cells = TraceRecorder::new()
+        .record_step(&mut emulator, warrior_id)?
+        .into_iter()
+        .map(|(addr, _before, after)| (addr, after))
+        .collect();
+    let process_queue = emulator.read_process_queue(warrior_id)?;
+
+    Ok(StepOutcome { mutated_cells, process_queue })
+}
@@ -0,0 +1,95 @@
+use redcode::Opcode;
+
+/// Backend-IR category a native code generator would lower an opcode to,
+/// analogous to YJIT's `Op::Add`/`Op::Load`/`Op::Store`/`Op::CondJump`.
+///
+/// This only classifies the opcode itself. Addressing-mode side effects
+/// (predecrement/postincrement) and the evaluated A/B pointers stay dynamic
+/// regardless of this tag, since they depend on runtime core contents rather
+/// than the decoded instruction alone.
+///
+/// There is deliberately no `IrBlock`/trace-compiler built on top of this
+/// yet. Core War requires strict cycle-by-cycle round robin across every
+/// active process of every warrior (including siblings an already-running
+/// warrior spawns with `spl`), so a native backend cannot simply run several
+/// of one warrior's instructions back-to-back without re-checking after each
+/// one whether some other process was due to interleave — the same
+/// constraint the `// TODO(jespy) compare this behavior w/ pmars` note on
+/// [`super::dispatch::Emulator::run`] is tracking. Straight-line tracing past
+/// a single instruction needs that scheduling question settled first, so
+/// this chunk stops at per-opcode classification: a real multi-instruction
+/// compiled block, its core-address-to-blocks invalidation map, and the
+/// actual machine-code emission are left for a follow-up once there's a way
+/// to build and run generated code in this environment to check it.
+#[allow(
+    dead_code,
+    reason = "not yet consumed by a native backend; see the module doc \
+              comment for why that's left for a follow-up"
+)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IrOp {
+    /// No effect past operand evaluation: [`Opcode::Dat`]
+    Discard,
+    /// Store the A-instruction's field(s) into the B-target: [`Opcode::Mov`]
+    Store,
+    /// `A-value + B-value`, modulo core size: [`Opcode::Add`]
+    Add,
+    /// `B-value - A-value`, modulo core size: [`Opcode::Sub`]
+    Sub,
+    /// `A-value * B-value`, modulo core size: [`Opcode::Mul`]
+    Mul,
+    /// `B-value / A-value`; division by zero skips the store and the PC+1
+    /// enqueue rather than producing a value: [`Opcode::Div`]
+    Div,
+    /// `B-value % A-value`; division by zero skips the store and the PC+1
+    /// enqueue rather than producing a value: [`Opcode::Mod`]
+    Mod,
+    /// Unconditional jump to the A-pointer: [`Opcode::Jmp`]
+    Jump,
+    /// Jump to the A-pointer if the B-value is (non-)zero, otherwise advance:
+    /// [`Opcode::Jmz`], [`Opcode::Jmn`]
+    CondJump,
+    /// Decrement the B-target, then jump to the A-pointer if the result is
+    /// non-zero, otherwise advance: [`Opcode::Djn`]
+    DecrementAndCondJump,
+    /// Enqueue PC+1 and the A-pointer: [`Opcode::Spl`]
+    Split,
+    /// Compare two fields, skipping the next instruction on a match:
+    /// [`Opcode::Slt`], [`Opcode::Cmp`], [`Opcode::Seq`], [`Opcode::Sne`]
+    Compare,
+    /// Advance with no other effect: [`Opcode::Nop`]
+    Advance,
+    /// Read a P-space cell into a core field: [`Opcode::Ldp`]
+    LoadPspace,
+    /// Write a core field into a P-space cell: [`Opcode::Stp`]
+    StorePspace,
+}
+
+/// Classifies `opcode` into the [`IrOp`] category a native backend would
+/// lower it to. Exhaustive over [`Opcode`], so adding a new opcode to
+/// `redcode` without updating this is a compile error rather than a silent
+/// gap.
+#[allow(
+    dead_code,
+    reason = "not yet consumed by a native backend; see IrOp's doc comment \
+              for why that's left for a follow-up"
+)]
+pub const fn lower_opcode(opcode: Opcode) -> IrOp {
+    match opcode {
+        Opcode::Dat => IrOp::Discard,
+        Opcode::Mov => IrOp::Store,
+        Opcode::Add => IrOp::Add,
+        Opcode::Sub => IrOp::Sub,
+        Opcode::Mul => IrOp::Mul,
+        Opcode::Div => IrOp::Div,
+        Opcode::Mod => IrOp::Mod,
+        Opcode::Jmp => IrOp::Jump,
+        Opcode::Jmz | Opcode::Jmn => IrOp::CondJump,
+        Opcode::Djn => IrOp::DecrementAndCondJump,
+        Opcode::Spl => IrOp::Split,
+        Opcode::Slt | Opcode::Cmp | Opcode::Seq | Opcode::Sne => IrOp::Compare,
+        Opcode::Nop => IrOp::Advance,
+        Opcode::Ldp => IrOp::LoadPspace,
+        Opcode::Stp => IrOp::StorePspace,
+    }
+}
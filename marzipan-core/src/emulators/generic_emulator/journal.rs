@@ -0,0 +1,259 @@
+use std::collections::HashSet;
+
+use redcode::CompleteInstruction;
+
+/// One previously-applied core write, enough to undo it.
+enum CoreUndo {
+    /// A single cell was overwritten through [`JournaledCore::get_mut`]
+    Cell {
+        /// The cell that was written to
+        addr: usize,
+        /// The value that was in the cell beforehand
+        previous: CompleteInstruction,
+    },
+    /// The entire core was swapped out through [`JournaledCore::replace_all`]
+    Replaced {
+        /// The cells that were in the core beforehand
+        previous: Vec<CompleteInstruction>,
+    },
+}
+
+/// One active snapshot's undo log.
+///
+/// `journaled_cells` tracks which addresses already have a `Cell` undo
+/// recorded since the last `Replaced` entry (or since the frame started): a
+/// tight self-modifying loop that rewrites the same handful of cells
+/// thousands of times in one round still only ever journals each cell's
+/// pre-frame value once, so undo cost stays proportional to the cells
+/// actually touched rather than the number of writes made to them.
+#[derive(Default)]
+struct SnapshotFrame {
+    /// Undo records in the order they were recorded
+    undo: Vec<CoreUndo>,
+    /// Addresses already holding a `Cell` undo in `undo`, since the last
+    /// `Replaced` entry or the start of the frame
+    journaled_cells: HashSet<usize>,
+}
+
+/// In-core instruction storage that records undo information for any active
+/// snapshots.
+///
+/// A snapshot is an entry on a stack of undo logs: [`Self::push_snapshot_frame`]
+/// starts one, and the first mutation of each cell afterward appends an undo
+/// record to the innermost log until [`Self::pop_snapshot_frame`] replays and discards it.
+/// With no active snapshot (an empty stack), mutations are as cheap as a
+/// plain `Vec`: recording is a single empty-stack check.
+pub struct JournaledCore {
+    /// The instructions currently stored in the core
+    cells: Vec<CompleteInstruction>,
+    /// Number of times each cell has been handed out through
+    /// [`Self::get_mut`] or restored by an undo, reset to all zeros whenever
+    /// `epoch` changes. Paired with `epoch`, forms the cheap cache key
+    /// returned by [`Self::cell_version`].
+    cell_writes: Vec<u64>,
+    /// Bumped whenever every cell's `cell_writes` counter is reset at once
+    /// (by [`Self::replace_all`] or an undo that restores one), so a version
+    /// cached from before the reset can never alias one from after it even if
+    /// the per-cell counter happens to match.
+    epoch: u64,
+    /// Stack of undo logs, one per active snapshot, innermost (most recently
+    /// taken) last
+    journal: Vec<SnapshotFrame>,
+}
+
+impl JournaledCore {
+    /// Constructs a core initialized with `cells`, with no active snapshots
+    pub fn new(cells: Vec<CompleteInstruction>) -> Self {
+        let cell_writes = vec![0; cells.len()];
+        Self {
+            cells,
+            cell_writes,
+            epoch: 0,
+            journal: Vec::new(),
+        }
+    }
+
+    /// Gets a mutable reference to a cell, journaling its prior value if a
+    /// snapshot is active and this is the first write to `idx` in that
+    /// snapshot. Returns `None` if `idx` is out of bounds.
+    pub fn get_mut(&mut self, idx: usize) -> Option<&mut CompleteInstruction> {
+        let previous = *self.cells.get(idx)?;
+        if let Some(frame) = self.journal.last_mut() {
+            if frame.journaled_cells.insert(idx) {
+                frame.undo.push(CoreUndo::Cell { addr: idx, previous });
+            }
+        }
+        if let Some(writes) = self.cell_writes.get_mut(idx) {
+            *writes = writes.wrapping_add(1);
+        }
+        self.cells.get_mut(idx)
+    }
+
+    /// Replaces every cell in the core with `cells`, journaling the entire
+    /// previous contents as a single undo record if a snapshot is active.
+    ///
+    /// This is for bulk operations like resetting the core, which are
+    /// already `O(core_size)`; recording one record this size doesn't change
+    /// that cost. Clears the active frame's per-cell dedup tracking, since a
+    /// cell written again after this point needs its value from the new
+    /// `cells` journaled, not its value from before the frame started.
+    pub fn replace_all(&mut self, cells: Vec<CompleteInstruction>) {
+        self.cell_writes = vec![0; cells.len()];
+        self.epoch = self.epoch.wrapping_add(1);
+        let previous = core::mem::replace(&mut self.cells, cells);
+        if let Some(frame) = self.journal.last_mut() {
+            frame.journaled_cells.clear();
+            frame.undo.push(CoreUndo::Replaced { previous });
+        }
+    }
+
+    /// Returns an opaque version tag for the cell at `idx`, changing any time
+    /// its contents are written through [`Self::get_mut`], or it is swapped
+    /// out in bulk by [`Self::replace_all`] or an undo. `None` if `idx` is out
+    /// of bounds.
+    ///
+    /// Used by [`super::compiled::CompiledTable`] to know when a cached
+    /// dispatch handler needs to be re-resolved from the cell's current
+    /// opcode rather than reused.
+    pub fn cell_version(&self, idx: usize) -> Option<(u64, u64)> {
+        self.cell_writes.get(idx).map(|&writes| (self.epoch, writes))
+    }
+
+    /// Starts a new snapshot: mutations from now on are undone by a matching
+    /// [`Self::pop_snapshot_frame`]
+    pub fn push_snapshot_frame(&mut self) {
+        self.journal.push(SnapshotFrame::default());
+    }
+
+    /// Undoes and discards the innermost snapshot's log. Returns `false` if
+    /// no snapshot is active.
+    pub fn pop_snapshot_frame(&mut self) -> bool {
+        let Some(frame) = self.journal.pop() else {
+            return false;
+        };
+        for undo in frame.undo.into_iter().rev() {
+            match undo {
+                CoreUndo::Cell { addr, previous } => {
+                    if let Some(cell) = self.cells.get_mut(addr) {
+                        *cell = previous;
+                    }
+                    if let Some(writes) = self.cell_writes.get_mut(addr) {
+                        *writes = writes.wrapping_add(1);
+                    }
+                }
+                CoreUndo::Replaced { previous } => {
+                    self.cell_writes = vec![0; previous.len()];
+                    self.epoch = self.epoch.wrapping_add(1);
+                    self.cells = previous;
+                }
+            }
+        }
+        true
+    }
+}
+
+impl core::ops::Deref for JournaledCore {
+    type Target = [CompleteInstruction];
+
+    /// Plain reads never need to be undone, so they bypass the journal
+    /// entirely via this `Deref`. There is deliberately no `DerefMut`:
+    /// mutation always goes through [`JournaledCore::get_mut`] or
+    /// [`JournaledCore::replace_all`] so it can be journaled.
+    fn deref(&self) -> &Self::Target {
+        &self.cells
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use redcode::{AddrMode, Instruction, Modifier};
+
+    use super::JournaledCore;
+
+    fn instr(a_field: u32) -> redcode::CompleteInstruction {
+        redcode::CompleteInstruction {
+            instr: Instruction {
+                opcode: redcode::Opcode::Dat,
+                modifier: Modifier::F,
+                a_addr_mode: AddrMode::Immediate,
+                b_addr_mode: AddrMode::Immediate,
+            },
+            a_field,
+            b_field: 0,
+        }
+    }
+
+    #[test]
+    fn mutation_without_a_snapshot_is_not_journaled_but_still_applies() {
+        let mut core = JournaledCore::new(vec![instr(0); 4]);
+        core.get_mut(1).unwrap().a_field = 42;
+        assert_eq!(core[1].a_field, 42);
+        assert!(!core.pop_snapshot_frame());
+    }
+
+    #[test]
+    fn rollback_undoes_cell_writes_since_the_snapshot() {
+        let mut core = JournaledCore::new(vec![instr(0); 4]);
+        core.push_snapshot_frame();
+        core.get_mut(1).unwrap().a_field = 42;
+        core.get_mut(2).unwrap().a_field = 7;
+        assert!(core.pop_snapshot_frame());
+        assert_eq!(core[1].a_field, 0);
+        assert_eq!(core[2].a_field, 0);
+    }
+
+    #[test]
+    fn rollback_undoes_a_bulk_replace() {
+        let mut core = JournaledCore::new(vec![instr(0); 2]);
+        core.push_snapshot_frame();
+        core.replace_all(vec![instr(99); 2]);
+        assert!(core.pop_snapshot_frame());
+        assert_eq!(core[0].a_field, 0);
+        assert_eq!(core[1].a_field, 0);
+    }
+
+    #[test]
+    fn repeated_writes_to_one_cell_undo_to_its_pre_frame_value() {
+        let mut core = JournaledCore::new(vec![instr(0); 2]);
+        core.push_snapshot_frame();
+        for write in 1..=1000 {
+            core.get_mut(0).unwrap().a_field = write;
+        }
+        assert!(core.pop_snapshot_frame());
+        assert_eq!(
+            core[0].a_field, 0,
+            "only the value from before the first write in a frame should \
+             ever be journaled for a given cell, so undoing restores that \
+             value regardless of how many times it was overwritten after"
+        );
+    }
+
+    #[test]
+    fn a_bulk_replace_mid_frame_gets_its_own_cell_undo_for_later_writes() {
+        let mut core = JournaledCore::new(vec![instr(0); 2]);
+        core.push_snapshot_frame();
+        core.get_mut(0).unwrap().a_field = 1;
+        core.replace_all(vec![instr(99); 2]);
+        core.get_mut(0).unwrap().a_field = 2;
+        assert!(core.pop_snapshot_frame());
+        assert_eq!(
+            core[0].a_field, 0,
+            "undoing a frame containing a write, then a bulk replace, then \
+             another write to the same cell should land back on the value \
+             from before the frame started, not the replace's fill value"
+        );
+    }
+
+    #[test]
+    fn nested_snapshots_undo_independently() {
+        let mut core = JournaledCore::new(vec![instr(0); 2]);
+        core.push_snapshot_frame();
+        core.get_mut(0).unwrap().a_field = 1;
+        core.push_snapshot_frame();
+        core.get_mut(0).unwrap().a_field = 2;
+        assert!(core.pop_snapshot_frame());
+        assert_eq!(core[0].a_field, 1);
+        assert!(core.pop_snapshot_frame());
+        assert_eq!(core[0].a_field, 0);
+    }
+}
@@ -1,6 +1,6 @@
-use redcode::{AddrMode, CompleteInstruction, Instruction};
+use redcode::{AddrMode, Instruction};
 
-use super::offset;
+use super::{journal::JournaledCore, offset};
 use crate::{
     emulator_core::{EmulatorError, EmulatorResult},
     CoreAddr,
@@ -42,8 +42,10 @@ pub struct RegisterValue {
 /// Evaluate the A and B operands according to the operand modifier
 ///
 /// Because `PostIncrement` may modify the core core, the values in
-/// [`RegisterValue`] are not guaranteed to match the in-core values.  
+/// [`RegisterValue`] are not guaranteed to match the in-core values.
 ///
+// TODO: indirect addressing here resolves across the full core; it doesn't
+// yet clip to `CoreSettings::read_limit`/`write_limit` as ICWS '94 requires.
 /// # Errors
 ///
 /// Returns [`EmulatorError::InternalError`] in exceptional circumstances.
@@ -51,7 +53,7 @@ pub struct RegisterValue {
 /// field values exceed `core_size - 1`.
 pub fn evaluate(
     pc: CoreAddr,
-    core: &mut [CompleteInstruction],
+    core: &mut JournaledCore,
 ) -> EmulatorResult<RegisterValues> {
     let size = core.len();
 
@@ -79,10 +81,10 @@ pub fn evaluate(
     // the a_field of the current instruction
     match cur.instr.a_addr_mode {
         AddrMode::PredecA => {
-            decrement(&mut core[a_indirect_index].a_field, size)?;
+            decrement(&mut field_mut(core, a_indirect_index)?.a_field, size)?;
         }
         AddrMode::PredecB => {
-            decrement(&mut core[a_indirect_index].b_field, size)?;
+            decrement(&mut field_mut(core, a_indirect_index)?.b_field, size)?;
         }
         _ => {}
     };
@@ -108,10 +110,10 @@ pub fn evaluate(
     // the a_field of the current instruction
     match cur.instr.a_addr_mode {
         AddrMode::PostincA => {
-            increment(&mut core[a_indirect_index].a_field, size)?;
+            increment(&mut field_mut(core, a_indirect_index)?.a_field, size)?;
         }
         AddrMode::PostincB => {
-            increment(&mut core[a_indirect_index].b_field, size)?;
+            increment(&mut field_mut(core, a_indirect_index)?.b_field, size)?;
         }
         _ => {}
     };
@@ -132,10 +134,10 @@ pub fn evaluate(
     // the b_field of the current instruction
     match cur.instr.b_addr_mode {
         AddrMode::PredecA => {
-            decrement(&mut core[b_indirect_index].a_field, size)?;
+            decrement(&mut field_mut(core, b_indirect_index)?.a_field, size)?;
         }
         AddrMode::PredecB => {
-            decrement(&mut core[b_indirect_index].b_field, size)?;
+            decrement(&mut field_mut(core, b_indirect_index)?.b_field, size)?;
         }
         _ => {}
     };
@@ -161,10 +163,10 @@ pub fn evaluate(
     // the b_field of the current instruction
     match cur.instr.b_addr_mode {
         AddrMode::PostincA => {
-            increment(&mut core[b_indirect_index].a_field, size)?;
+            increment(&mut field_mut(core, b_indirect_index)?.a_field, size)?;
         }
         AddrMode::PostincB => {
-            increment(&mut core[b_indirect_index].b_field, size)?;
+            increment(&mut field_mut(core, b_indirect_index)?.b_field, size)?;
         }
         _ => {}
     };
@@ -191,6 +193,18 @@ pub fn evaluate(
     })
 }
 
+/// Gets a mutable reference to a core cell at `idx`, journaling the mutation
+/// if a snapshot is active. `JournaledCore` has no `DerefMut`, so this is the
+/// only way to write to it.
+fn field_mut(
+    core: &mut JournaledCore,
+    idx: usize,
+) -> EmulatorResult<&mut redcode::CompleteInstruction> {
+    core.get_mut(idx).ok_or(EmulatorError::InternalError(
+        "index out of bounds for core of this size",
+    ))
+}
+
 /// Validate an address, lookup the value at that address, and add one modulo
 /// core size
 fn increment(val: &mut CoreAddr, size: usize) -> EmulatorResult<()> {
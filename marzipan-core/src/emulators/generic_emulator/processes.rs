@@ -7,6 +7,35 @@ use crate::{
     CoreAddr, CoreSettings,
 };
 
+/// One previously-applied process-queue mutation, enough to undo it.
+enum ProcessUndo {
+    /// [`ProcessQueueSet::push_back`] added a value; undo by removing the one
+    /// it added
+    Pushed {
+        /// Index of the queue that was pushed to
+        warrior_idx: usize,
+    },
+    /// [`ProcessQueueSet::pop`] removed the front value; undo by restoring it
+    Popped {
+        /// Index of the queue that was popped from
+        warrior_idx: usize,
+        /// The value that was at the front of the queue
+        value: CoreAddr,
+    },
+    /// [`ProcessQueueSet::replace_queue`] replaced a single queue
+    Replaced {
+        /// Index of the queue that was replaced
+        warrior_idx: usize,
+        /// The queue's contents beforehand
+        previous: VecDeque<CoreAddr>,
+    },
+    /// [`ProcessQueueSet::reset_queues`] replaced every queue
+    AllReset {
+        /// Every queue's contents beforehand
+        previous: Vec<VecDeque<CoreAddr>>,
+    },
+}
+
 /// Stores program counters for each warrior, up to a capacity defined by
 /// `CoreSettings`.
 pub struct ProcessQueueSet {
@@ -15,6 +44,9 @@ pub struct ProcessQueueSet {
     /// Number of processes beyond which additional calls to
     /// [`ProcessQueueSet::push_back`] will have no effect.
     max_processes: usize,
+    /// Stack of undo logs, one per active snapshot, innermost (most recently
+    /// taken) last
+    journal: Vec<Vec<ProcessUndo>>,
 }
 
 impl ProcessQueueSet {
@@ -22,14 +54,19 @@ impl ProcessQueueSet {
     ///
     /// Returns an [`InternalError`] if `warrior_id` is invalid
     pub fn pop(&mut self, warrior_id: u64) -> EmulatorResult<Option<CoreAddr>> {
-        Ok(self
+        let warrior_idx = convert_warrior_id(warrior_id)?;
+        let popped = self
             .queues
-            .get_mut(convert_warrior_id(warrior_id)?)
+            .get_mut(warrior_idx)
             .ok_or(EmulatorError::InternalError(
                 "tried to pop from the process queue for a warrior that \
                  doesn't exist",
             ))?
-            .pop_front())
+            .pop_front();
+        if let Some(value) = popped {
+            self.record(ProcessUndo::Popped { warrior_idx, value });
+        }
+        Ok(popped)
     }
 
     /// Adds a program counter for a warrior if that warrior is not already at
@@ -39,31 +76,108 @@ impl ProcessQueueSet {
         value: CoreAddr,
         warrior_id: u64,
     ) -> EmulatorResult<()> {
-        let pq = self.queues.get_mut(convert_warrior_id(warrior_id)?).ok_or(
+        let warrior_idx = convert_warrior_id(warrior_id)?;
+        let pq = self.queues.get_mut(warrior_idx).ok_or(
             EmulatorError::InternalError(
                 "a process queue doesn't exist for this warrior",
             ),
         )?;
         if pq.len() < self.max_processes {
             pq.push_back(value);
+            self.record(ProcessUndo::Pushed { warrior_idx });
         }
         Ok(())
     }
 
     /// Empties the process queues for all warriors
     pub fn reset_queues(&mut self) {
-        self.queues = vec![VecDeque::new(); self.queues.len()];
+        let len = self.queues.len();
+        let previous =
+            core::mem::replace(&mut self.queues, vec![VecDeque::new(); len]);
+        self.record(ProcessUndo::AllReset { previous });
+    }
+
+    /// Appends `undo` to the innermost active snapshot's log, if any
+    fn record(&mut self, undo: ProcessUndo) {
+        if let Some(frame) = self.journal.last_mut() {
+            frame.push(undo);
+        }
+    }
+
+    /// Starts a new snapshot: mutations from now on are undone by a matching
+    /// [`Self::pop_snapshot_frame`]
+    pub fn push_snapshot_frame(&mut self) {
+        self.journal.push(Vec::new());
+    }
+
+    /// Undoes and discards the innermost snapshot's log. Returns `false` if
+    /// no snapshot is active.
+    pub fn pop_snapshot_frame(&mut self) -> bool {
+        let Some(frame) = self.journal.pop() else {
+            return false;
+        };
+        for undo in frame.into_iter().rev() {
+            match undo {
+                ProcessUndo::Pushed { warrior_idx } => {
+                    if let Some(q) = self.queues.get_mut(warrior_idx) {
+                        q.pop_back();
+                    }
+                }
+                ProcessUndo::Popped { warrior_idx, value } => {
+                    if let Some(q) = self.queues.get_mut(warrior_idx) {
+                        q.push_front(value);
+                    }
+                }
+                ProcessUndo::Replaced {
+                    warrior_idx,
+                    previous,
+                } => {
+                    if let Some(q) = self.queues.get_mut(warrior_idx) {
+                        *q = previous;
+                    }
+                }
+                ProcessUndo::AllReset { previous } => {
+                    self.queues = previous;
+                }
+            }
+        }
+        true
+    }
+
+    /// Returns whether a warrior currently has no queued processes
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`EmulatorError::InternalError`] if `warrior_id` is invalid
+    pub fn queue_is_empty(&self, warrior_id: u64) -> EmulatorResult<bool> {
+        let warrior_idx = convert_warrior_id(warrior_id)?;
+        self.queues.get(warrior_idx).map(VecDeque::is_empty).ok_or(
+            EmulatorError::InternalError(
+                "tried to check process queue emptiness for a warrior that \
+                 doesn't exist",
+            ),
+        )
     }
 
     /// Returns the set of `warrior_ids` with non-empty process queues
     pub fn active_warriors(&self) -> Vec<u64> {
-        return self
-            .queues
-            .iter()
-            .zip(0..)
-            .filter(|&(pq, _)| !pq.is_empty())
-            .map(|(_, idx)| idx)
-            .collect();
+        let mut out = Vec::new();
+        self.active_warriors_into(&mut out);
+        out
+    }
+
+    /// Writes the set of `warrior_id`s with non-empty process queues into
+    /// `out`, clearing it first. Returns the number of warriors written.
+    pub fn active_warriors_into(&self, out: &mut Vec<u64>) -> usize {
+        out.clear();
+        out.extend(
+            self.queues
+                .iter()
+                .zip(0..)
+                .filter(|&(pq, _)| !pq.is_empty())
+                .map(|(_, idx)| idx),
+        );
+        out.len()
     }
 
     /// Replace the process queue for a warrior with the input queue, in order
@@ -91,11 +205,19 @@ impl ProcessQueueSet {
         } else {
             Ok(())
         }?;
+        let warrior_idx = convert_warrior_id(warrior_id)?;
         let queue = self
             .queues
-            .get_mut(convert_warrior_id(warrior_id)?)
+            .get_mut(warrior_idx)
             .ok_or(EmulatorError::InternalError("Invalid Warrior id"))?;
-        *queue = VecDeque::from(process_queue.iter().copied().collect_vec());
+        let previous = core::mem::replace(
+            queue,
+            VecDeque::from(process_queue.iter().copied().collect_vec()),
+        );
+        self.record(ProcessUndo::Replaced {
+            warrior_idx,
+            previous,
+        });
         Ok(())
     }
 
@@ -111,6 +233,7 @@ impl ProcessQueueSet {
             ],
             max_processes: usize::try_from(settings.processes)
                 .unwrap_or_default(),
+            journal: Vec::new(),
         }
     }
 
@@ -120,14 +243,31 @@ impl ProcessQueueSet {
     ///
     /// Returns an [`EmulatorError::InternalError`] if `warrior_id` is invalid
     pub fn read_queue(&self, warrior_id: u64) -> EmulatorResult<Vec<CoreAddr>> {
+        let mut out = Vec::new();
+        self.read_queue_into(warrior_id, &mut out)?;
+        Ok(out)
+    }
+
+    /// Writes the entire content of a warrior's process queue in order into
+    /// `out`, clearing it first. Returns the number of entries written.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`EmulatorError::InternalError`] if `warrior_id` is invalid
+    pub fn read_queue_into(
+        &self,
+        warrior_id: u64,
+        out: &mut Vec<CoreAddr>,
+    ) -> EmulatorResult<usize> {
         let q = self.queues.get(convert_warrior_id(warrior_id)?).ok_or(
             EmulatorError::InternalError(
                 "attempting to read the process queue of a warrior with no \
                  process queue",
             ),
         )?;
-        let v: Vec<u32> = q.iter().copied().collect_vec();
-        Ok(v)
+        out.clear();
+        out.extend(q.iter().copied());
+        Ok(out.len())
     }
 }
 
@@ -1,10 +1,36 @@
 use std::collections::HashMap;
 
+use super::state_blob;
 use crate::{
     emulator_core::{EmulatorError, EmulatorResult},
     CoreAddr,
 };
 
+/// One previously-applied pspace mutation, enough to undo it.
+enum PspaceUndo {
+    /// [`PSpace::write`] overwrote a single value
+    Write {
+        /// The warrior whose pspace was written to
+        warrior_id: u64,
+        /// The location that was written to
+        location: CoreAddr,
+        /// The value that was there beforehand
+        previous: CoreAddr,
+    },
+    /// A full reset (e.g. re-initializing pspace for a new pin mapping)
+    /// replaced every mapping and value
+    Replaced {
+        /// `pspace_size` beforehand
+        pspace_size: u32,
+        /// `warrior_to_pin` beforehand
+        warrior_to_pin: HashMap<u64, u64>,
+        /// `zero_index_values` beforehand
+        zero_index_values: HashMap<u64, CoreAddr>,
+        /// `pin_to_pspace` beforehand
+        pin_to_pspace: HashMap<u64, Vec<CoreAddr>>,
+    },
+}
+
 /// Contains all pspace mappings and values for all warriors
 #[derive(Default)]
 pub struct PSpace {
@@ -17,8 +43,11 @@ pub struct PSpace {
     zero_index_values: HashMap<u64, CoreAddr>,
     /// pspace buffers indexed by the pins from `warrior_to_pin`
     ///
-    /// The 0 index in each pspace buffer is unused.  
+    /// The 0 index in each pspace buffer is unused.
     pin_to_pspace: HashMap<u64, Vec<CoreAddr>>,
+    /// Stack of undo logs, one per active snapshot, innermost (most recently
+    /// taken) last
+    journal: Vec<Vec<PspaceUndo>>,
 }
 
 impl PSpace {
@@ -30,17 +59,41 @@ impl PSpace {
         }
     }
 
-    /// read a value from `location` in the pspace owned by `warrior_id`
+    /// Wraps `location` into a valid pspace index, the same way `offset`
+    /// wraps core addresses: any index is valid, it's just taken modulo the
+    /// pspace size.
+    ///
+    /// Used by [`Self::read`] and [`Self::write`], so both reduce an index
+    /// the same way before checking for the warrior-private index zero.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`EmulatorError::InternalError`] if `pspace_size` is zero,
+    /// so no pspace address would be valid.
+    pub(crate) fn wrap_index(
+        &self,
+        location: CoreAddr,
+    ) -> EmulatorResult<CoreAddr> {
+        location.checked_rem(self.pspace_size).ok_or(
+            EmulatorError::InternalError(
+                "pspace_size is zero, so no pspace address is valid",
+            ),
+        )
+    }
+
+    /// read a value from `location` (taken modulo the pspace size) in the
+    /// pspace owned by `warrior_id`
     ///
     /// # Errors
     ///
     /// Returns an [`EmulatorError::InternalError`] if this warrior has no
-    /// pspace, or if `location` is not a valid pspace address
+    /// pspace, or if the pspace size is zero
     pub fn read(
         &self,
         location: CoreAddr,
         warrior_id: u64,
     ) -> EmulatorResult<CoreAddr> {
+        let location = self.wrap_index(location)?;
         match location {
             0 => self.zero_index_values.get(&warrior_id),
             _ => self
@@ -53,29 +106,109 @@ impl PSpace {
         .copied()
     }
 
-    /// Write 'value' to 'location' in the pspsace owned by `warrior_id`
+    /// Write 'value' to 'location' (taken modulo the pspace size) in the
+    /// pspace owned by `warrior_id`
     ///
     /// # Errors
     ///
     /// Returns an [`EmulatorError::InternalError`] if this warrior has no
-    /// pspace, or if `location` is not a valid pspace address
+    /// pspace, or if the pspace size is zero
     pub fn write(
         &mut self,
         location: CoreAddr,
         value: CoreAddr,
         warrior_id: u64,
     ) -> EmulatorResult<()> {
-        let location = match location {
+        let location = self.wrap_index(location)?;
+        let previous = self.read(location, warrior_id)?;
+        self.set_raw(location, value, warrior_id);
+        self.record(PspaceUndo::Write {
+            warrior_id,
+            location,
+            previous,
+        });
+        Ok(())
+    }
+
+    /// Overwrites a single value without journaling the previous one.
+    ///
+    /// Used both by [`Self::write`] (which journals separately) and by
+    /// [`Self::pop_snapshot_frame`] to apply an undo without recording a new
+    /// one.
+    fn set_raw(&mut self, location: CoreAddr, value: CoreAddr, warrior_id: u64) {
+        let slot = match location {
             0 => self.zero_index_values.get_mut(&warrior_id),
             _ => self
                 .warrior_to_pin
                 .get(&warrior_id)
                 .and_then(|pin| self.pin_to_pspace.get_mut(pin))
                 .and_then(|pspace| pspace.get_mut(location as usize)),
+        };
+        if let Some(slot) = slot {
+            *slot = value;
         }
-        .ok_or(EmulatorError::InternalError("invalid pspace reference"))?;
-        *location = value;
-        Ok(())
+    }
+
+    /// Replaces every pspace mapping and value as if this were freshly
+    /// constructed with `pspace_size`, journaling the previous state as a
+    /// single undo record if a snapshot is active.
+    pub fn replace_all(&mut self, pspace_size: u32) {
+        let previous_pspace_size =
+            core::mem::replace(&mut self.pspace_size, pspace_size);
+        let previous_warrior_to_pin = core::mem::take(&mut self.warrior_to_pin);
+        let previous_zero_index_values =
+            core::mem::take(&mut self.zero_index_values);
+        let previous_pin_to_pspace = core::mem::take(&mut self.pin_to_pspace);
+        self.record(PspaceUndo::Replaced {
+            pspace_size: previous_pspace_size,
+            warrior_to_pin: previous_warrior_to_pin,
+            zero_index_values: previous_zero_index_values,
+            pin_to_pspace: previous_pin_to_pspace,
+        });
+    }
+
+    /// Appends `undo` to the innermost active snapshot's log, if any
+    fn record(&mut self, undo: PspaceUndo) {
+        if let Some(frame) = self.journal.last_mut() {
+            frame.push(undo);
+        }
+    }
+
+    /// Starts a new snapshot: mutations from now on are undone by a matching
+    /// [`Self::pop_snapshot_frame`]
+    pub fn push_snapshot_frame(&mut self) {
+        self.journal.push(Vec::new());
+    }
+
+    /// Undoes and discards the innermost snapshot's log. Returns `false` if
+    /// no snapshot is active.
+    pub fn pop_snapshot_frame(&mut self) -> bool {
+        let Some(frame) = self.journal.pop() else {
+            return false;
+        };
+        for undo in frame.into_iter().rev() {
+            match undo {
+                PspaceUndo::Write {
+                    warrior_id,
+                    location,
+                    previous,
+                } => {
+                    self.set_raw(location, previous, warrior_id);
+                }
+                PspaceUndo::Replaced {
+                    pspace_size,
+                    warrior_to_pin,
+                    zero_index_values,
+                    pin_to_pspace,
+                } => {
+                    self.pspace_size = pspace_size;
+                    self.warrior_to_pin = warrior_to_pin;
+                    self.zero_index_values = zero_index_values;
+                    self.pin_to_pspace = pin_to_pspace;
+                }
+            }
+        }
+        true
     }
 
     /// Allocates a pspace identified by this pin
@@ -117,4 +250,234 @@ impl PSpace {
         self.zero_index_values.insert(warrior_id, 0);
         Ok(())
     }
+
+    /// Returns an iterator over every allocated pspace buffer, as `(pin,
+    /// contents)` pairs
+    pub fn pins(&self) -> impl Iterator<Item = (u64, &[CoreAddr])> + '_ {
+        self.pin_to_pspace
+            .iter()
+            .map(|(&pin, buf)| (pin, buf.as_slice()))
+    }
+
+    /// Returns an iterator over every warrior's pin assignment, as
+    /// `(warrior_id, pin)` pairs
+    pub fn warrior_pins(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+        self.warrior_to_pin
+            .iter()
+            .map(|(&warrior_id, &pin)| (warrior_id, pin))
+    }
+
+    /// Returns an iterator over every warrior's private `pspace[0]` value, as
+    /// `(warrior_id, value)` pairs
+    pub fn zero_values(&self) -> impl Iterator<Item = (u64, CoreAddr)> + '_ {
+        self.zero_index_values
+            .iter()
+            .map(|(&warrior_id, &value)| (warrior_id, value))
+    }
+
+    /// Replaces every pspace mapping and value with the given contents,
+    /// journaling the previous state as a single undo record if a snapshot
+    /// is active.
+    ///
+    /// Used to restore state captured with [`Self::pins`],
+    /// [`Self::warrior_pins`], and [`Self::zero_values`], e.g. by
+    /// [`crate::EmulatorCore::deserialize_state`].
+    pub fn restore(
+        &mut self,
+        pspace_size: u32,
+        warrior_to_pin: HashMap<u64, u64>,
+        zero_index_values: HashMap<u64, CoreAddr>,
+        pin_to_pspace: HashMap<u64, Vec<CoreAddr>>,
+    ) {
+        let previous_pspace_size =
+            core::mem::replace(&mut self.pspace_size, pspace_size);
+        let previous_warrior_to_pin =
+            core::mem::replace(&mut self.warrior_to_pin, warrior_to_pin);
+        let previous_zero_index_values = core::mem::replace(
+            &mut self.zero_index_values,
+            zero_index_values,
+        );
+        let previous_pin_to_pspace =
+            core::mem::replace(&mut self.pin_to_pspace, pin_to_pspace);
+        self.record(PspaceUndo::Replaced {
+            pspace_size: previous_pspace_size,
+            warrior_to_pin: previous_warrior_to_pin,
+            zero_index_values: previous_zero_index_values,
+            pin_to_pspace: previous_pin_to_pspace,
+        });
+    }
+
+    /// Captures every pin buffer, warrior pin assignment, and warrior-private
+    /// `pspace[0]` value into a serializable blob, independent of any
+    /// particular core or warrior load-out.
+    ///
+    /// Meant for callers that persist or clone pspace on its own: e.g. an
+    /// evolver cloning a known-good pspace into a batch of candidate
+    /// matches, or a tournament runner persisting cross-round pspace state
+    /// between separate process invocations.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`EmulatorError::InternalError`] if there are too many pins
+    /// or warriors to fit the length prefixes used by this format.
+    pub fn snapshot(&self) -> EmulatorResult<Vec<u8>> {
+        let pins = self
+            .pins()
+            .map(|(pin, buf)| (pin, buf.to_vec()))
+            .collect::<Vec<_>>();
+        let warrior_pins = self.warrior_pins().collect::<Vec<_>>();
+        let zero_values = self.zero_values().collect::<Vec<_>>();
+        state_blob::encode_pspace(
+            self.pspace_size,
+            &pins,
+            &warrior_pins,
+            &zero_values,
+        )
+    }
+
+    /// Replaces every pspace mapping and value with the contents of a blob
+    /// produced by [`Self::snapshot`], journaling the previous state as a
+    /// single undo record if a snapshot frame is active.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`EmulatorError::InvalidParam`] if `blob`'s pspace size
+    /// doesn't match this pspace's configured size, or if `blob` is
+    /// truncated or malformed.
+    pub fn restore_snapshot(&mut self, blob: &[u8]) -> EmulatorResult<()> {
+        let decoded = state_blob::decode_pspace(blob, self.pspace_size)?;
+        self.restore(
+            decoded.pspace_size,
+            decoded.warrior_to_pin,
+            decoded.zero_index_values,
+            decoded.pin_to_pspace,
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[cfg_attr(coverage, coverage(off))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wrap_index_reduces_modulo_pspace_size() {
+        let pspace = PSpace::new(10);
+        assert_eq!(pspace.wrap_index(0).unwrap(), 0);
+        assert_eq!(pspace.wrap_index(9).unwrap(), 9);
+        assert_eq!(pspace.wrap_index(10).unwrap(), 0);
+        assert_eq!(pspace.wrap_index(23).unwrap(), 3);
+    }
+
+    #[test]
+    fn wrap_index_errors_when_pspace_size_is_zero() {
+        let pspace = PSpace::new(0);
+        assert!(pspace.wrap_index(0).is_err());
+    }
+
+    #[test]
+    fn wrap_index_is_not_commensurate_with_core_size_reduction() {
+        // When pspace_size doesn't evenly divide core_size, reducing modulo
+        // core_size and then modulo pspace_size can disagree with reducing
+        // modulo pspace_size directly, as the note in `ldp_op` warns. With a
+        // core_size of 7 and pspace_size of 3: x = 8 reduces to 1 (mod 7),
+        // then 1 (mod 3); but reduced directly, 8 (mod 3) is 2.
+        let core_size: CoreAddr = 7;
+        let pspace_size: CoreAddr = 3;
+        let pspace = PSpace::new(pspace_size);
+        let x: CoreAddr = 8;
+        let via_core_size = pspace.wrap_index(x % core_size).unwrap();
+        let direct = pspace.wrap_index(x).unwrap();
+        assert_ne!(via_core_size, direct);
+    }
+
+    #[test]
+    fn write_then_read_round_trips_for_a_non_zero_index() {
+        let mut pspace = PSpace::new(10);
+        pspace.add_pspace(1).unwrap();
+        pspace.assign_pspace(0, 1).unwrap();
+        pspace.write(5, 42, 0).unwrap();
+        assert_eq!(pspace.read(5, 0).unwrap(), 42);
+    }
+
+    #[test]
+    fn index_zero_is_private_to_each_warrior_sharing_a_pin() {
+        let mut pspace = PSpace::new(10);
+        pspace.add_pspace(1).unwrap();
+        pspace.assign_pspace(0, 1).unwrap();
+        pspace.assign_pspace(1, 1).unwrap();
+        pspace.write(0, 99, 0).unwrap();
+        assert_eq!(pspace.read(0, 0).unwrap(), 99);
+        assert_eq!(pspace.read(0, 1).unwrap(), 0);
+    }
+
+    #[test]
+    fn non_zero_indices_are_shared_between_warriors_on_the_same_pin() {
+        let mut pspace = PSpace::new(10);
+        pspace.add_pspace(1).unwrap();
+        pspace.assign_pspace(0, 1).unwrap();
+        pspace.assign_pspace(1, 1).unwrap();
+        pspace.write(5, 7, 0).unwrap();
+        assert_eq!(pspace.read(5, 1).unwrap(), 7);
+    }
+
+    #[test]
+    fn read_and_write_error_for_a_warrior_with_no_pspace() {
+        let pspace = PSpace::new(10);
+        assert!(pspace.read(5, 0).is_err());
+        let mut pspace = pspace;
+        assert!(pspace.write(5, 1, 0).is_err());
+    }
+
+    #[test]
+    fn popping_a_snapshot_frame_undoes_writes_made_within_it() {
+        let mut pspace = PSpace::new(10);
+        pspace.add_pspace(1).unwrap();
+        pspace.assign_pspace(0, 1).unwrap();
+        pspace.write(5, 1, 0).unwrap();
+
+        pspace.push_snapshot_frame();
+        pspace.write(5, 2, 0).unwrap();
+        pspace.write(0, 3, 0).unwrap();
+        assert!(pspace.pop_snapshot_frame());
+
+        assert_eq!(pspace.read(5, 0).unwrap(), 1);
+        assert_eq!(pspace.read(0, 0).unwrap(), 0);
+    }
+
+    #[test]
+    fn popping_with_no_active_snapshot_frame_returns_false() {
+        let mut pspace = PSpace::new(10);
+        assert!(!pspace.pop_snapshot_frame());
+    }
+
+    #[test]
+    fn snapshot_round_trips_through_restore_snapshot() {
+        let mut pspace = PSpace::new(10);
+        pspace.add_pspace(1).unwrap();
+        pspace.assign_pspace(0, 1).unwrap();
+        pspace.assign_pspace(1, 1).unwrap();
+        pspace.write(5, 7, 0).unwrap();
+        pspace.write(0, 42, 0).unwrap();
+
+        let blob = pspace.snapshot().unwrap();
+
+        let mut restored = PSpace::new(10);
+        restored.restore_snapshot(&blob).unwrap();
+
+        assert_eq!(restored.read(5, 0).unwrap(), 7);
+        assert_eq!(restored.read(5, 1).unwrap(), 7);
+        assert_eq!(restored.read(0, 0).unwrap(), 42);
+        assert_eq!(restored.read(0, 1).unwrap(), 0);
+    }
+
+    #[test]
+    fn restore_snapshot_rejects_a_mismatched_pspace_size() {
+        let mut pspace = PSpace::new(10);
+        let blob = pspace.snapshot().unwrap();
+
+        let mut mismatched = PSpace::new(5);
+        assert!(mismatched.restore_snapshot(&blob).is_err());
+    }
 }
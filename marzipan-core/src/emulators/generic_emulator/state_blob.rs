@@ -0,0 +1,503 @@
+use std::collections::HashMap;
+
+use crate::{
+    emulator_core::{CoreSettings, EmulatorError, EmulatorResult},
+    BytecodeInstructionIdentifier, CoreAddr,
+};
+
+/// Appends a big-endian `u8` to `out`
+fn push_u8(out: &mut Vec<u8>, val: u8) {
+    out.push(val);
+}
+
+/// Appends a big-endian `u32` to `out`
+fn push_u32(out: &mut Vec<u8>, val: u32) {
+    out.extend_from_slice(&val.to_be_bytes());
+}
+
+/// Appends a big-endian `u64` to `out`
+fn push_u64(out: &mut Vec<u8>, val: u64) {
+    out.extend_from_slice(&val.to_be_bytes());
+}
+
+/// Converts a count to a `u32` for the length-prefixes in the blob format
+///
+/// # Errors
+///
+/// Returns an [`EmulatorError::InternalError`] if `count` doesn't fit a `u32`
+fn count_to_u32(count: usize, msg: &'static str) -> EmulatorResult<u32> {
+    u32::try_from(count).map_or(Err(EmulatorError::InternalError(msg)), Ok)
+}
+
+/// A read cursor over a [`encode`]d blob
+struct Reader<'a> {
+    /// The blob being read
+    bytes: &'a [u8],
+    /// Offset of the next unread byte
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    /// Starts reading `bytes` from the beginning
+    const fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    /// Takes and returns the next `len` bytes
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`EmulatorError::InvalidParam`] if fewer than `len` bytes
+    /// remain
+    fn take(&mut self, len: usize) -> EmulatorResult<&'a [u8]> {
+        let end = self.pos.checked_add(len).ok_or(
+            EmulatorError::InvalidParam(
+                "serialized state blob's length overflowed while parsing",
+            ),
+        )?;
+        let slice = self.bytes.get(self.pos..end).ok_or(
+            EmulatorError::InvalidParam(
+                "serialized state blob is truncated or malformed",
+            ),
+        )?;
+        self.pos = end;
+        Ok(slice)
+    }
+
+    /// Reads the next byte
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`EmulatorError::InvalidParam`] if no bytes remain
+    fn u8(&mut self) -> EmulatorResult<u8> {
+        let [byte] = self.take(1)?.try_into().map_err(|_err| {
+            EmulatorError::InternalError(
+                "impossible: take(1) didn't return exactly one byte",
+            )
+        })?;
+        Ok(byte)
+    }
+
+    /// Reads the next big-endian `u32`
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`EmulatorError::InvalidParam`] if fewer than 4 bytes remain
+    fn u32(&mut self) -> EmulatorResult<u32> {
+        let arr: [u8; 4] = self.take(4)?.try_into().map_err(|_err| {
+            EmulatorError::InternalError(
+                "impossible: take(4) didn't return exactly four bytes",
+            )
+        })?;
+        Ok(u32::from_be_bytes(arr))
+    }
+
+    /// Reads the next big-endian `u64`
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`EmulatorError::InvalidParam`] if fewer than 8 bytes remain
+    fn u64(&mut self) -> EmulatorResult<u64> {
+        let arr: [u8; 8] = self.take(8)?.try_into().map_err(|_err| {
+            EmulatorError::InternalError(
+                "impossible: take(8) didn't return exactly eight bytes",
+            )
+        })?;
+        Ok(u64::from_be_bytes(arr))
+    }
+}
+
+/// The parsed, header-validated contents of an [`encode`]d blob, ready to be
+/// converted back into redcode types and applied to a live emulator.
+pub struct DecodedState {
+    /// One `(bytecode, a_field, b_field)` triple per core address, in order
+    pub core: Vec<(BytecodeInstructionIdentifier, CoreAddr, CoreAddr)>,
+    /// One process queue per warrior id, in order, next process first
+    pub queues: Vec<Vec<CoreAddr>>,
+    /// Number of elements in each pin's pspace buffer
+    pub pspace_size: u32,
+    /// pspace buffer contents, keyed by pin
+    pub pin_to_pspace: HashMap<u64, Vec<CoreAddr>>,
+    /// The pin assigned to each warrior that has one
+    pub warrior_to_pin: HashMap<u64, u64>,
+    /// The warrior-private `pspace[0]` value for each warrior that has one
+    pub zero_index_values: HashMap<u64, CoreAddr>,
+}
+
+/// Appends the pin buffers, pin assignments, and zero-index values shared by
+/// [`encode`] and [`encode_pspace`]. Neither format's `pspace_size` is
+/// written here: [`encode`] already has one in its header, and
+/// [`encode_pspace`] writes its own before calling this.
+///
+/// # Errors
+///
+/// Returns an [`EmulatorError::InternalError`] if any of the inputs are too
+/// large to fit the length prefixes used by this format (e.g. more than
+/// `u32::MAX` pins).
+fn push_pspace_pins(
+    out: &mut Vec<u8>,
+    pins: &[(u64, Vec<CoreAddr>)],
+    warrior_pins: &[(u64, u64)],
+    zero_values: &[(u64, CoreAddr)],
+) -> EmulatorResult<()> {
+    push_u32(out, count_to_u32(pins.len(), "too many pspace pins")?);
+    for (pin, buf) in pins {
+        push_u64(out, *pin);
+        push_u32(
+            out,
+            count_to_u32(buf.len(), "a pspace buffer is impossibly long")?,
+        );
+        for &value in buf {
+            push_u32(out, value);
+        }
+    }
+
+    push_u32(
+        out,
+        count_to_u32(warrior_pins.len(), "too many pspace pin assignments")?,
+    );
+    for &(warrior_id, pin) in warrior_pins {
+        push_u64(out, warrior_id);
+        push_u64(out, pin);
+    }
+
+    push_u32(
+        out,
+        count_to_u32(zero_values.len(), "too many pspace[0] values")?,
+    );
+    for &(warrior_id, value) in zero_values {
+        push_u64(out, warrior_id);
+        push_u32(out, value);
+    }
+
+    Ok(())
+}
+
+/// Parses the pin buffers, pin assignments, and zero-index values written by
+/// [`push_pspace_pins`], validating every pin buffer's length against
+/// `pspace_size`.
+///
+/// # Errors
+///
+/// Returns an [`EmulatorError::InvalidParam`] if `reader` is truncated,
+/// malformed, or contains a pspace buffer whose length isn't `pspace_size`
+#[allow(
+    clippy::type_complexity,
+    reason = "internal decode helper returning a handful of maps, not part \
+              of this module's public API"
+)]
+fn decode_pspace_pins(
+    reader: &mut Reader,
+    pspace_size: u32,
+) -> EmulatorResult<(
+    HashMap<u64, Vec<CoreAddr>>,
+    HashMap<u64, u64>,
+    HashMap<u64, CoreAddr>,
+)> {
+    let pin_count = reader.u32()?;
+    let mut pin_to_pspace = HashMap::new();
+    for _ in 0..pin_count {
+        let pin = reader.u64()?;
+        let raw_len = reader.u32()?;
+        if raw_len != pspace_size {
+            return Err(EmulatorError::InvalidParam(
+                "a pspace buffer in the serialized state doesn't match this \
+                 core's configured pspace_size",
+            ));
+        }
+        let mut buf = Vec::with_capacity(buf_len_usize(raw_len)?);
+        for _ in 0..raw_len {
+            buf.push(reader.u32()?);
+        }
+        pin_to_pspace.insert(pin, buf);
+    }
+
+    let warrior_pin_count = reader.u32()?;
+    let mut warrior_to_pin = HashMap::new();
+    for _ in 0..warrior_pin_count {
+        let warrior_id = reader.u64()?;
+        let pin = reader.u64()?;
+        warrior_to_pin.insert(warrior_id, pin);
+    }
+
+    let zero_value_count = reader.u32()?;
+    let mut zero_index_values = HashMap::new();
+    for _ in 0..zero_value_count {
+        let warrior_id = reader.u64()?;
+        let value = reader.u32()?;
+        zero_index_values.insert(warrior_id, value);
+    }
+
+    Ok((pin_to_pspace, warrior_to_pin, zero_index_values))
+}
+
+/// Encodes a full core's observable state into a self-describing blob: a
+/// header of `bytecode_format`/`core_size`/`pspace_size`/`warriors`/
+/// `processes`, followed by every core cell's `(bytecode, a_field, b_field)`
+/// triple, one process queue per warrior, and all pspace pin buffers and
+/// assignments. [`decode`] parses this layout back.
+///
+/// # Errors
+///
+/// Returns an [`EmulatorError::InternalError`] if any of the inputs are too
+/// large to fit the length prefixes used by this format (e.g. more than
+/// `u32::MAX` pins).
+pub fn encode(
+    config: &CoreSettings,
+    core: &[(BytecodeInstructionIdentifier, CoreAddr, CoreAddr)],
+    queues: &[Vec<CoreAddr>],
+    pins: &[(u64, Vec<CoreAddr>)],
+    warrior_pins: &[(u64, u64)],
+    zero_values: &[(u64, CoreAddr)],
+) -> EmulatorResult<Vec<u8>> {
+    let mut out = Vec::new();
+
+    match config.bytecode_format {
+        Some(format) => {
+            push_u8(&mut out, 1);
+            push_u32(
+                &mut out,
+                count_to_u32(
+                    format.len(),
+                    "bytecode_format string is impossibly long",
+                )?,
+            );
+            out.extend_from_slice(format.as_bytes());
+        }
+        None => push_u8(&mut out, 0),
+    }
+    push_u64(&mut out, config.core_size);
+    push_u64(&mut out, config.pspace_size);
+    push_u64(&mut out, config.warriors);
+    push_u64(&mut out, config.processes);
+
+    for &(bytecode, a_field, b_field) in core {
+        push_u32(&mut out, bytecode);
+        push_u32(&mut out, a_field);
+        push_u32(&mut out, b_field);
+    }
+
+    for queue in queues {
+        push_u32(
+            &mut out,
+            count_to_u32(queue.len(), "a process queue is impossibly long")?,
+        );
+        for &addr in queue {
+            push_u32(&mut out, addr);
+        }
+    }
+
+    push_pspace_pins(&mut out, pins, warrior_pins, zero_values)?;
+
+    Ok(out)
+}
+
+/// Encodes just a pspace's observable state (every pin's buffer, every
+/// warrior's pin assignment, and every warrior's private `pspace[0]` value)
+/// into a self-describing blob, without the core cells, process queues, or
+/// header fields that [`encode`] also captures.
+///
+/// Meant for callers that persist or clone pspace on its own, independent of
+/// any particular core or warrior load-out: e.g. an evolver cloning a
+/// known-good pspace into a batch of candidate matches, or a tournament
+/// runner persisting cross-round pspace state between separate process
+/// invocations. [`decode_pspace`] parses this layout back.
+///
+/// # Errors
+///
+/// Returns an [`EmulatorError::InternalError`] if any of the inputs are too
+/// large to fit the length prefixes used by this format (e.g. more than
+/// `u32::MAX` pins).
+pub fn encode_pspace(
+    pspace_size: u32,
+    pins: &[(u64, Vec<CoreAddr>)],
+    warrior_pins: &[(u64, u64)],
+    zero_values: &[(u64, CoreAddr)],
+) -> EmulatorResult<Vec<u8>> {
+    let mut out = Vec::new();
+    push_u32(&mut out, pspace_size);
+    push_pspace_pins(&mut out, pins, warrior_pins, zero_values)?;
+    Ok(out)
+}
+
+/// Parses `bytes` as produced by [`encode_pspace`], validating that its
+/// embedded pspace size matches `pspace_size`.
+///
+/// # Errors
+///
+/// Returns an [`EmulatorError::InvalidParam`] if the blob's pspace size
+/// doesn't match `pspace_size`, or if `bytes` is truncated or malformed.
+pub fn decode_pspace(
+    bytes: &[u8],
+    pspace_size: u32,
+) -> EmulatorResult<DecodedPspace> {
+    let mut reader = Reader::new(bytes);
+    let blob_pspace_size = reader.u32()?;
+    if blob_pspace_size != pspace_size {
+        return Err(EmulatorError::InvalidParam(
+            "serialized pspace's size doesn't match this emulator's \
+             configured pspace_size",
+        ));
+    }
+    let (pin_to_pspace, warrior_to_pin, zero_index_values) =
+        decode_pspace_pins(&mut reader, pspace_size)?;
+    Ok(DecodedPspace {
+        pspace_size,
+        pin_to_pspace,
+        warrior_to_pin,
+        zero_index_values,
+    })
+}
+
+/// The parsed, validated contents of an [`encode_pspace`]d blob
+pub struct DecodedPspace {
+    /// Number of elements in each pin's pspace buffer
+    pub pspace_size: u32,
+    /// pspace buffer contents, keyed by pin
+    pub pin_to_pspace: HashMap<u64, Vec<CoreAddr>>,
+    /// The pin assigned to each warrior that has one
+    pub warrior_to_pin: HashMap<u64, u64>,
+    /// The warrior-private `pspace[0]` value for each warrior that has one
+    pub zero_index_values: HashMap<u64, CoreAddr>,
+}
+
+/// Parses `bytes` as produced by [`encode`], validating the header against
+/// `config` before parsing the body.
+///
+/// # Errors
+///
+/// Returns [`EmulatorError::UnsupportedFeature`] if the blob's own
+/// `bytecode_format` is absent or empty, since its raw bytecode identifiers
+/// aren't guaranteed portable even to another instance of the same
+/// implementation. Returns [`EmulatorError::InvalidParam`] if the embedded
+/// `bytecode_format` or core geometry disagrees with `config`, or if `bytes`
+/// is truncated or malformed.
+pub fn decode(
+    bytes: &[u8],
+    config: &CoreSettings,
+) -> EmulatorResult<DecodedState> {
+    let mut reader = Reader::new(bytes);
+
+    let format_len = match reader.u8()? {
+        0 => None,
+        _ => Some(reader.u32()?),
+    };
+    let format = match format_len {
+        None => None,
+        Some(len) => Some(reader.take(usize::try_from(len).map_or(
+            Err(EmulatorError::InvalidParam(
+                "serialized state's bytecode_format length doesn't fit a \
+                 usize",
+            )),
+            Ok,
+        )?)?),
+    };
+    match format {
+        None => {
+            return Err(EmulatorError::UnsupportedFeature(
+                "serialized state has no bytecode_format, so its bytecode \
+                 identifiers aren't guaranteed portable",
+            ));
+        }
+        Some(format) if format.is_empty() => {
+            return Err(EmulatorError::UnsupportedFeature(
+                "serialized state has an empty bytecode_format, so its \
+                 bytecode identifiers aren't guaranteed portable",
+            ));
+        }
+        Some(format)
+            if config.bytecode_format.map(str::as_bytes) != Some(format) =>
+        {
+            return Err(EmulatorError::InvalidParam(
+                "serialized state's bytecode_format doesn't match this \
+                 emulator's",
+            ));
+        }
+        Some(_) => {}
+    }
+
+    let core_size = reader.u64()?;
+    let pspace_size = reader.u64()?;
+    let warriors = reader.u64()?;
+    let processes = reader.u64()?;
+    if core_size != config.core_size
+        || pspace_size != config.pspace_size
+        || warriors != config.warriors
+        || processes != config.processes
+    {
+        return Err(EmulatorError::InvalidParam(
+            "serialized state's core geometry doesn't match this \
+             emulator's",
+        ));
+    }
+
+    let cell_count = usize::try_from(core_size).map_or(
+        Err(EmulatorError::InternalError("impossibly large core_size")),
+        Ok,
+    )?;
+    let mut core = Vec::with_capacity(cell_count);
+    for _ in 0..cell_count {
+        let bytecode = reader.u32()?;
+        let a_field = reader.u32()?;
+        let b_field = reader.u32()?;
+        core.push((bytecode, a_field, b_field));
+    }
+
+    let warrior_count = usize::try_from(warriors).map_or(
+        Err(EmulatorError::InternalError("impossibly large warriors count")),
+        Ok,
+    )?;
+    let mut queues = Vec::with_capacity(warrior_count);
+    for _ in 0..warrior_count {
+        let raw_len = reader.u32()?;
+        if u64::from(raw_len) > processes {
+            return Err(EmulatorError::InvalidParam(
+                "a process queue in the serialized state is longer than \
+                 this core's configured max processes",
+            ));
+        }
+        let len = usize::try_from(raw_len).map_or(
+            Err(EmulatorError::InternalError(
+                "process queue length doesn't fit a usize",
+            )),
+            Ok,
+        )?;
+        let mut queue = Vec::with_capacity(len);
+        for _ in 0..len {
+            queue.push(reader.u32()?);
+        }
+        queues.push(queue);
+    }
+
+    let pspace_size_u32 = u32::try_from(pspace_size).map_or(
+        Err(EmulatorError::InternalError("impossibly large pspace_size")),
+        Ok,
+    )?;
+
+    let (pin_to_pspace, warrior_to_pin, zero_index_values) =
+        decode_pspace_pins(&mut reader, pspace_size_u32)?;
+
+    Ok(DecodedState {
+        core,
+        queues,
+        pspace_size: pspace_size_u32,
+        pin_to_pspace,
+        warrior_to_pin,
+        zero_index_values,
+    })
+}
+
+/// Converts a pspace buffer's length, already confirmed to equal
+/// `pspace_size`, to a `usize` for `Vec::with_capacity`
+///
+/// # Errors
+///
+/// Returns an [`EmulatorError::InternalError`] if `len` doesn't fit a `usize`
+fn buf_len_usize(len: u32) -> EmulatorResult<usize> {
+    usize::try_from(len).map_or(
+        Err(EmulatorError::InternalError(
+            "pspace buffer length doesn't fit a usize",
+        )),
+        Ok,
+    )
+}
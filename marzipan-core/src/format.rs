@@ -0,0 +1,242 @@
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::ops::RangeInclusive;
+
+use redcode::{AddrMode, CompleteInstruction};
+
+use crate::{
+    emulator_core::{EmulatorCore, EmulatorResult},
+    CoreAddr,
+};
+
+/// The letter case used for opcode and modifier mnemonics.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum MnemonicCase {
+    /// `MOV.I`
+    Upper,
+    /// `mov.i`
+    Lower,
+}
+
+/// How a field's value is rendered.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum FieldStyle {
+    /// The value actually stored in the field, always in `0..core_size`.
+    Normalized,
+    /// A value past the midpoint of the core is rendered as its negative
+    /// distance from zero instead, e.g. a field one below `core_size`
+    /// prints as `-1`.
+    Signed,
+}
+
+/// Governs [`Formatter::format_instruction`]'s output.
+#[non_exhaustive]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct FormatOptions {
+    /// Letter case for opcode and modifier mnemonics
+    pub mnemonic_case: MnemonicCase,
+    /// Whether to always print the `.modifier`, even when it's the one
+    /// [`redcode::default_modifiers`] would have assigned
+    pub always_print_modifier: bool,
+    /// How field values are rendered
+    pub field_style: FieldStyle,
+}
+
+impl FormatOptions {
+    /// The canonical ICWS'94 load-file rendering: uppercase mnemonics, the
+    /// modifier always printed, fields normalized to `0..core_size`.
+    pub const DEFAULT_OPTIONS: Self = Self {
+        mnemonic_case: MnemonicCase::Upper,
+        always_print_modifier: true,
+        field_style: FieldStyle::Normalized,
+    };
+
+    /// Renders mnemonics in lowercase, e.g. `mov.i` instead of `MOV.I`.
+    #[must_use]
+    pub const fn lowercase_mnemonics(mut self) -> Self {
+        self.mnemonic_case = MnemonicCase::Lower;
+        self
+    }
+
+    /// Omits the `.modifier` when it matches [`redcode::default_modifiers`]
+    /// for the instruction's opcode and addressing modes.
+    #[must_use]
+    pub const fn collapse_default_modifier(mut self) -> Self {
+        self.always_print_modifier = false;
+        self
+    }
+
+    /// Renders field values past the midpoint of the core as negative
+    /// offsets instead of normalizing them to `0..core_size`.
+    #[must_use]
+    pub const fn signed_fields(mut self) -> Self {
+        self.field_style = FieldStyle::Signed;
+        self
+    }
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self::DEFAULT_OPTIONS
+    }
+}
+
+/// Renders a [`CompleteInstruction`] as text in one concrete notation.
+///
+/// `core_size` is needed only to render [`FieldStyle::Signed`] fields; it's
+/// ignored under [`FieldStyle::Normalized`].
+pub trait Formatter {
+    /// Renders `instr` as text, governed by `options`.
+    fn format_instruction(
+        &self,
+        instr: &CompleteInstruction,
+        core_size: u64,
+        options: FormatOptions,
+    ) -> String;
+}
+
+/// Renders instructions as the canonical ICWS'94 load-file notation, e.g.
+/// `MOV.I $2, @2`.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Icws94Formatter;
+
+impl Formatter for Icws94Formatter {
+    fn format_instruction(
+        &self,
+        instr: &CompleteInstruction,
+        core_size: u64,
+        options: FormatOptions,
+    ) -> String {
+        let opcode = mnemonic(&instr.instr.opcode.to_string(), options);
+        let modifier = modifier_text(instr, options);
+        let a_field = field_text(instr.a_field, core_size, options.field_style);
+        let b_field = field_text(instr.b_field, core_size, options.field_style);
+        format!(
+            "{opcode}{modifier} {}{a_field}, {}{b_field}",
+            instr.instr.a_addr_mode, instr.instr.b_addr_mode,
+        )
+    }
+}
+
+/// Renders instructions with addressing modes spelled out as words instead
+/// of sigils, e.g. `MOV.I direct(2), indirect_b(2)`.
+///
+/// Borrows the "multiple syntaxes for the same decoded instruction" idea
+/// from x86 disassemblers that can render intel/AT&T/etc. styles; there's
+/// no equivalent convention for Redcode, so this just trades the
+/// [`Icws94Formatter`]'s terse sigils for a form that doesn't require
+/// memorizing them.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct VerboseFormatter;
+
+impl Formatter for VerboseFormatter {
+    fn format_instruction(
+        &self,
+        instr: &CompleteInstruction,
+        core_size: u64,
+        options: FormatOptions,
+    ) -> String {
+        let opcode = mnemonic(&instr.instr.opcode.to_string(), options);
+        let modifier = modifier_text(instr, options);
+        let a_field = field_text(instr.a_field, core_size, options.field_style);
+        let b_field = field_text(instr.b_field, core_size, options.field_style);
+        format!(
+            "{opcode}{modifier} {}({a_field}), {}({b_field})",
+            addr_mode_name(instr.instr.a_addr_mode),
+            addr_mode_name(instr.instr.b_addr_mode),
+        )
+    }
+}
+
+/// Applies [`FormatOptions::mnemonic_case`] to an already-rendered mnemonic.
+fn mnemonic(text: &str, options: FormatOptions) -> String {
+    match options.mnemonic_case {
+        MnemonicCase::Upper => text.to_string(),
+        MnemonicCase::Lower => text.to_lowercase(),
+    }
+}
+
+/// Renders `.modifier`, or an empty string if `options` says to collapse it
+/// and it matches the opcode/addressing-mode default.
+fn modifier_text(
+    instr: &CompleteInstruction,
+    options: FormatOptions,
+) -> String {
+    let is_default = instr.instr.modifier
+        == redcode::default_modifiers(
+            instr.instr.opcode,
+            instr.instr.a_addr_mode,
+            instr.instr.b_addr_mode,
+        );
+    if !options.always_print_modifier && is_default {
+        return String::new();
+    }
+    let modifier = mnemonic(&instr.instr.modifier.to_string(), options);
+    format!(".{modifier}")
+}
+
+/// Renders a field value per `style`, given the core size it's modulo.
+fn field_text(value: CoreAddr, core_size: u64, style: FieldStyle) -> String {
+    match style {
+        FieldStyle::Normalized => value.to_string(),
+        FieldStyle::Signed => {
+            let half = core_size / 2;
+            if u64::from(value) > half {
+                let core_size = i64::try_from(core_size).unwrap_or(i64::MAX);
+                let distance = core_size.saturating_sub(i64::from(value));
+                format!("-{distance}")
+            } else {
+                value.to_string()
+            }
+        }
+    }
+}
+
+/// The word [`VerboseFormatter`] uses in place of an [`AddrMode`]'s sigil.
+const fn addr_mode_name(mode: AddrMode) -> &'static str {
+    match mode {
+        AddrMode::Immediate => "immediate",
+        AddrMode::Direct => "direct",
+        AddrMode::IndirectA => "indirect_a",
+        AddrMode::IndirectB => "indirect_b",
+        AddrMode::PredecA => "predec_a",
+        AddrMode::PredecB => "predec_b",
+        AddrMode::PostincA => "postinc_a",
+        AddrMode::PostincB => "postinc_b",
+    }
+}
+
+/// Renders one line per address in `range`, each prefixed with its address
+/// column-aligned to the width of the largest address in the core, reading
+/// cells from `emulator` through [`EmulatorCore::read_core`] and
+/// [`EmulatorCore::bytecode_to_rc`].
+///
+/// # Errors
+///
+/// Returns [`crate::EmulatorError`]s as [`EmulatorCore::read_core`] or
+/// [`EmulatorCore::bytecode_to_rc`] would.
+pub fn dump(
+    emulator: &dyn EmulatorCore,
+    range: RangeInclusive<CoreAddr>,
+    formatter: &dyn Formatter,
+    options: FormatOptions,
+) -> EmulatorResult<String> {
+    let core_size = emulator.core_settings().core_size;
+    let width = core_size.saturating_sub(1).to_string().len();
+    let mut lines = Vec::new();
+    for addr in range {
+        let (bytecode, a_field, b_field) = emulator.read_core(addr)?;
+        let instr = CompleteInstruction {
+            instr: emulator.bytecode_to_rc(bytecode)?,
+            a_field,
+            b_field,
+        };
+        let rendered =
+            formatter.format_instruction(&instr, core_size, options);
+        lines.push(format!("{addr:>width$}  {rendered}"));
+    }
+    Ok(lines.join("\n"))
+}
@@ -29,6 +29,8 @@
 //!     pspace_size,
 //!     warriors,
 //!     process,
+//!     core_size, // read_limit: core_size means unrestricted
+//!     core_size, // write_limit: core_size means unrestricted
 //! )
 //! .unwrap();
 //! // Other emulator implementations might have a static pspace or even none at all
@@ -272,7 +274,8 @@ extern crate alloc;
 /// Contains the [`EmulatorCore`] trait for low-level emulator interactions
 mod emulator_core;
 pub use emulator_core::{
-    CoreSettings, EmulatorCore, EmulatorError, EmulatorResult,
+    CoreObserver, CoreSettings, EmulatorCore, EmulatorError, EmulatorResult,
+    RunControl, RunOutcome, SnapshotToken, StopConditions, StopReason,
 };
 
 /// An offset into an emulator core, valid from 0 to `core_size - 1` inclusive.
@@ -290,3 +293,18 @@ pub type BytecodeInstructionIdentifier = u32;
 
 /// Emulator implementations.
 pub mod emulators;
+
+/// A breakpoint/watchpoint debugging layer built on [`CoreObserver`].
+pub mod debugger;
+
+/// A snapshot-diff execution-trace recorder for differential testing.
+pub mod trace;
+
+/// Renders decoded instructions and core regions as Redcode text, in a
+/// choice of output syntaxes.
+pub mod format;
+
+/// Writes an assembled warrior into an [`EmulatorCore`]'s core and seeds its
+/// process queue, the one piece of warrior loading this crate takes a
+/// position on.
+pub mod loader;
@@ -0,0 +1,50 @@
+use alloc::vec::Vec;
+
+use redcode::CompleteInstruction;
+
+use crate::{
+    emulator_core::{wrap_addr, EmulatorCore, EmulatorResult},
+    CoreAddr,
+};
+
+/// Writes `program` into `emulator`'s core starting at `base` and gives
+/// `warrior_id` a process queue of one, so it's ready to run.
+///
+/// This is the loop the [crate docs](crate) show calling
+/// [`EmulatorCore::rc_to_bytecode`] and [`EmulatorCore::write_core`] by hand,
+/// automated and with the `core_size` wraparound handled once via
+/// [`EmulatorCore::write_core_range`] instead of at each call site. `start` is
+/// the offset from `base` of the first instruction to execute (see
+/// [`redcode::Warrior::start`]); the process queue is seeded with
+/// `base + start`, wrapped the same way.
+///
+/// As the [crate docs](crate) note, [`EmulatorCore`] itself has no notion of
+/// warrior loading or where in the core a warrior belongs; this is the
+/// lowest layer of the "MARS" logic expected to sit on top of it.
+///
+/// # Errors
+///
+/// Returns [`crate::EmulatorError`]s as [`EmulatorCore::write_core_range`] or
+/// [`EmulatorCore::replace_process_queue`] would.
+pub fn load_warrior(
+    emulator: &mut dyn EmulatorCore,
+    warrior_id: u64,
+    base: CoreAddr,
+    program: &[CompleteInstruction],
+    start: CoreAddr,
+) -> EmulatorResult<()> {
+    let core_size = emulator.core_settings().core_size;
+    let cells = program
+        .iter()
+        .map(|instr| {
+            (
+                emulator.rc_to_bytecode(instr.instr),
+                instr.a_field,
+                instr.b_field,
+            )
+        })
+        .collect::<Vec<_>>();
+    emulator.write_core_range(base, &cells)?;
+    let start_addr = wrap_addr(base, u64::from(start), core_size)?;
+    emulator.replace_process_queue(warrior_id, &[start_addr])
+}
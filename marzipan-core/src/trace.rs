@@ -0,0 +1,79 @@
+use alloc::vec::Vec;
+
+use crate::{
+    emulator_core::{EmulatorCore, EmulatorError, EmulatorResult},
+    BytecodeInstructionIdentifier, CoreAddr,
+};
+
+/// A core cell's contents, mirroring the `(opcode, a_field, b_field)` tuple
+/// [`EmulatorCore::read_core`] returns.
+pub type RawCell = (BytecodeInstructionIdentifier, CoreAddr, CoreAddr);
+
+/// Accumulates per-step core mutations across repeated
+/// [`TraceRecorder::record_step`] calls, producing a full execution trace for
+/// comparing two [`EmulatorCore`]s - e.g. this crate's emulator and a
+/// reference MARS implementation - instruction-by-instruction.
+#[derive(Debug, Clone, Default)]
+pub struct TraceRecorder {
+    /// One entry per [`TraceRecorder::record_step`] call so far, each
+    /// holding every cell that changed during that step.
+    trace: Vec<Vec<(CoreAddr, RawCell, RawCell)>>,
+}
+
+impl TraceRecorder {
+    /// Constructs a recorder with an empty trace.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Executes one step for `warrior_id` on `emulator`, appending every cell
+    /// mutation it caused (address, value before, value after) as a new
+    /// entry in the trace, and returns that same list of mutations.
+    ///
+    /// Diffs the whole core by reading every cell before and after the step
+    /// rather than hooking [`crate::CoreObserver`], since that trait's
+    /// `on_write` only reports which address changed, not its old or new
+    /// value. This costs a full `core_size` read before and after every
+    /// step, which is fine for a fuzzing or differential-testing harness but
+    /// not for the hot dispatch loop.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EmulatorError`]s as [`EmulatorCore::core_settings`],
+    /// [`EmulatorCore::read_core`], or [`EmulatorCore::step`] would.
+    pub fn record_step(
+        &mut self,
+        emulator: &mut dyn EmulatorCore,
+        warrior_id: u64,
+    ) -> EmulatorResult<Vec<(CoreAddr, RawCell, RawCell)>> {
+        let core_size = emulator.core_settings().core_size;
+        let mut before = Vec::new();
+        for idx in 0..core_size {
+            let addr = CoreAddr::try_from(idx).map_err(|_err| {
+                EmulatorError::InternalError("impossibly large core address")
+            })?;
+            before.push((addr, emulator.read_core(addr)?));
+        }
+
+        emulator.step(warrior_id)?;
+
+        let mut mutations = Vec::new();
+        for (addr, old) in before {
+            let new = emulator.read_core(addr)?;
+            if new != old {
+                mutations.push((addr, old, new));
+            }
+        }
+        self.trace.push(mutations.clone());
+        Ok(mutations)
+    }
+
+    /// Returns the full trace recorded so far: one entry per
+    /// [`TraceRecorder::record_step`] call, each holding that step's cell
+    /// mutations.
+    #[must_use]
+    pub fn trace(&self) -> &[Vec<(CoreAddr, RawCell, RawCell)>] {
+        &self.trace
+    }
+}
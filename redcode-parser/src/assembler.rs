@@ -0,0 +1,1213 @@
+//! A minimal two-pass Redcode source assembler.
+//!
+//! Unlike [`crate::loadfile_parser::parse`], which only accepts
+//! already-assembled loadfiles (bare opcodes with literal numeric fields),
+//! [`parse_source`] accepts source written the way warrior authors actually
+//! write it: with labels, `EQU` constants, and `FOR`/`ROF` loop expansion.
+//!
+//! Assembly happens in three stages:
+//!  1. `EQU` definitions are expanded as a textual substitution, left to
+//!     right, so later lines (including later `EQU`s) see the substituted
+//!     text of earlier ones.
+//!  2. `FOR <expr> ... ROF` blocks are expanded by matching each `FOR` to its
+//!     closing `ROF`, repeating the enclosed lines the requested number of
+//!     times and exposing the loop counter as a symbol local to that
+//!     repetition.
+//!  3. The expanded lines are assembled in two passes: the first builds a
+//!     symbol table mapping labels to the instruction index they precede,
+//!     and the second evaluates each field expression, resolving label
+//!     references to a relative offset (`symbol_value - current_index`) and
+//!     resolving `ORG`/`END` arguments to an absolute address.
+//!
+//! Field expressions support `+ - * / %`, parentheses, and symbol lookup,
+//! evaluated by a small recursive-descent evaluator.
+//!
+//! [`CustomOps`] lets a caller register a handler for a directive name
+//! [`parse_source`] doesn't know about natively, for house-rule pseudo-ops
+//! or experimental opcodes. [`ParseOptions`] stays a plain, `Copy`,
+//! `Eq`/`Hash` value type shared with [`crate::loadfile_parser`], so the
+//! registry is threaded as its own argument via
+//! [`parse_source_with_custom_ops`] rather than living on `ParseOptions`
+//! itself.
+//!
+//! This is the full assembler front-end for human-authored source: symbolic
+//! labels resolved to relative offsets, `EQU` constant substitution, `END`
+//! (optionally with a label or expression argument) as an alternative
+//! terminator to `ORG`, and `+ - * / %` expression operands with standard
+//! precedence and parentheses, all lowered directly into a
+//! [`RelaxedWarrior`] whose signed fields let label resolution happen ahead
+//! of a later `normalize`.
+
+use alloc::{
+    borrow::{Cow, ToOwned},
+    boxed::Box,
+    collections::BTreeMap,
+    format,
+    string::String,
+    vec::Vec,
+};
+
+use redcode::{default_modifiers, AddrMode, Instruction, RelaxedCompleteInstruction, RelaxedWarrior};
+
+use crate::{
+    loadfile_parser::ParseOptions,
+    loadfile_primitives::{addr_mode, modifier, opcode},
+};
+
+/// An error produced while assembling Redcode source.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AssemblerError {
+    /// A `FOR` with no matching `ROF`, at the 1-based line of the `FOR`
+    UnmatchedFor {
+        /// The 1-based source line of the unmatched `FOR`
+        line: usize,
+    },
+    /// A `ROF` with no matching `FOR`, at the 1-based line of the `ROF`
+    UnmatchedRof {
+        /// The 1-based source line of the unmatched `ROF`
+        line: usize,
+    },
+    /// A symbol used in an expression was never defined by a label, `EQU`,
+    /// or enclosing `FOR` loop counter
+    UndefinedSymbol {
+        /// The undefined symbol
+        name: String,
+        /// The 1-based source line referencing the symbol
+        line: usize,
+    },
+    /// A label (after any `&` loop-counter concatenation) was defined more
+    /// than once
+    DuplicateLabel {
+        /// The duplicated label, with any `&` already resolved to its
+        /// iteration number
+        name: String,
+        /// The 1-based source line of the second (and rejected) definition
+        line: usize,
+    },
+    /// A field, `FOR` count, `ORG`, or `END` expression couldn't be parsed
+    /// or evaluated
+    InvalidExpression {
+        /// The 1-based source line containing the expression
+        line: usize,
+        /// The text of the offending expression
+        text: String,
+    },
+    /// A line didn't match any recognized instruction, pseudo-op, or label
+    /// grammar
+    InvalidLine {
+        /// The 1-based source line that failed to assemble
+        line: usize,
+        /// A description of why the line was rejected
+        reason: String,
+    },
+}
+
+impl core::fmt::Display for AssemblerError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::UnmatchedFor { line } => {
+                write!(f, "line {line}: FOR with no matching ROF")
+            }
+            Self::UnmatchedRof { line } => {
+                write!(f, "line {line}: ROF with no matching FOR")
+            }
+            Self::UndefinedSymbol { name, line } => {
+                write!(f, "line {line}: undefined symbol \"{name}\"")
+            }
+            Self::DuplicateLabel { name, line } => {
+                write!(f, "line {line}: label \"{name}\" is already defined")
+            }
+            Self::InvalidExpression { line, text } => {
+                write!(f, "line {line}: invalid expression \"{text}\"")
+            }
+            Self::InvalidLine { line, reason } => {
+                write!(f, "line {line}: {reason}")
+            }
+        }
+    }
+}
+
+impl core::error::Error for AssemblerError {}
+
+impl AssemblerError {
+    /// The 1-based line this error is attached to.
+    #[must_use]
+    pub const fn line(&self) -> usize {
+        match self {
+            Self::UnmatchedFor { line }
+            | Self::UnmatchedRof { line }
+            | Self::UndefinedSymbol { line, .. }
+            | Self::DuplicateLabel { line, .. }
+            | Self::InvalidExpression { line, .. }
+            | Self::InvalidLine { line, .. } => *line,
+        }
+    }
+
+    /// Renders this error against `source` (the exact text passed to
+    /// [`parse_source`]), quoting the offending line underneath the
+    /// message, in the spirit of
+    /// [`crate::loadfile_parser::ParseError`]'s `Display` impl.
+    ///
+    /// [`Self::line`] tracks the original, unexpanded source through `EQU`
+    /// substitution and `FOR`/`ROF` expansion, so this always quotes the
+    /// line the author actually wrote, even when the error itself was
+    /// raised against expanded text. There's no caret under a specific
+    /// column, unlike [`crate::loadfile_parser::ParseError`]: unlike that
+    /// nom-driven parser, this assembler never tracks a column, only the
+    /// line an error occurred on.
+    #[must_use]
+    pub fn render(&self, source: &str) -> String {
+        let source_line =
+            source.lines().nth(self.line().saturating_sub(1)).unwrap_or("");
+        format!("{self}\n{source_line}")
+    }
+}
+
+/// What a [`CustomOpHandler`] does in place of the directive it matched.
+pub enum CustomOpOutcome {
+    /// Instructions to append in the directive's place. May be empty (the
+    /// directive is consumed but emits no code, like `PIN`) or contain more
+    /// than one (the directive expands into a short sequence).
+    Instructions(Vec<RelaxedCompleteInstruction>),
+    /// Resolve `start` to this value, as `ORG`/`END` would.
+    Start(i64),
+    /// Resolve `pin` to this value, as `PIN` would.
+    Pin(i64),
+}
+
+/// Context available to a [`CustomOpHandler`] while it is invoked.
+pub struct CustomOpContext<'a> {
+    /// The instruction index the next emitted instruction would occupy.
+    pub address: i64,
+    /// Resolves a label, `EQU` constant, or enclosing `FOR` loop counter to
+    /// its value, the same way the built-in operand expression evaluator
+    /// does. Returns `None` while counting instruction slots during the
+    /// first assembly pass, before labels are known; a handler's *slot
+    /// count* must not depend on resolved symbol values, only its field
+    /// values may.
+    pub resolve: &'a dyn Fn(&str) -> Option<i64>,
+    /// The options this assembly pass is running with.
+    pub options: ParseOptions,
+}
+
+/// A caller-supplied parse function for a non-standard directive, registered
+/// by name in a [`CustomOps`] registry.
+///
+/// Receives the raw argument text following the directive name and the
+/// current [`CustomOpContext`]. Returning `None` means "not mine", falling
+/// through to the built-in `ORG`/`END`/`PIN`/instruction handling.
+pub type CustomOpHandler =
+    dyn Fn(&str, &CustomOpContext<'_>) -> Option<CustomOpOutcome>;
+
+/// A registry of [`CustomOpHandler`]s keyed by directive name, for
+/// non-standard pseudo-ops or experimental opcodes that
+/// [`parse_source_with_custom_ops`] doesn't know about natively.
+#[derive(Default)]
+pub struct CustomOps {
+    /// Registered handlers, keyed by uppercased directive name
+    handlers: BTreeMap<String, Box<CustomOpHandler>>,
+}
+
+impl CustomOps {
+    /// An empty registry; every directive falls through to the built-in
+    /// `ORG`/`END`/`PIN`/instruction handling.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `handler` for `name`, matched case-insensitively.
+    #[must_use]
+    pub fn with_handler(
+        mut self,
+        name: &str,
+        handler: impl Fn(&str, &CustomOpContext<'_>) -> Option<CustomOpOutcome>
+            + 'static,
+    ) -> Self {
+        self.handlers.insert(name.to_uppercase(), Box::new(handler));
+        self
+    }
+
+    /// Looks up the handler registered for `name`, if any.
+    fn get(&self, name: &str) -> Option<&CustomOpHandler> {
+        self.handlers.get(&name.to_uppercase()).map(Box::as_ref)
+    }
+}
+
+/// Assembles Redcode source containing labels, `EQU` constants, and
+/// `FOR`/`ROF` loops into a [`RelaxedWarrior`].
+///
+/// [`ParseOptions`] governs the same instruction-level concerns as
+/// [`crate::loadfile_parser::parse`] (e.g. whether modifiers may be
+/// omitted); it has no effect on label, `EQU`, or `FOR`/`ROF` handling, which
+/// this function always supports.
+///
+/// # Errors
+///
+/// Returns an [`AssemblerError`] if a `FOR`/`ROF` pair is unbalanced, an
+/// expression references an undefined symbol, an expression can't be
+/// evaluated, or a line doesn't match the instruction or pseudo-op grammar.
+pub fn parse_source(
+    source: &str,
+    options: ParseOptions,
+) -> Result<RelaxedWarrior, AssemblerError> {
+    parse_source_with_custom_ops(source, options, &CustomOps::new())
+}
+
+/// Like [`parse_source`], but consults `custom_ops` for any directive name
+/// not recognized by the built-in `ORG`/`END`/`PIN`/instruction grammar (and
+/// ahead of it, so a registered handler may also override a built-in name).
+///
+/// # Errors
+///
+/// Returns the same errors as [`parse_source`].
+pub fn parse_source_with_custom_ops(
+    source: &str,
+    options: ParseOptions,
+    custom_ops: &CustomOps,
+) -> Result<RelaxedWarrior, AssemblerError> {
+    let substituted = substitute_equ(source);
+    let expanded = expand_for_rof(&substituted)?;
+
+    let mut labels: BTreeMap<String, i64> = BTreeMap::new();
+    let mut next_instr_index: i64 = 0;
+    for line in &expanded {
+        let (label, rest) = split_label(&line.text);
+        if let Some(label) = label {
+            let label = concatenate_ampersand(label, &line.locals);
+            if labels.contains_key(label.as_ref()) {
+                return Err(AssemblerError::DuplicateLabel {
+                    name: label.into_owned(),
+                    line: line.source_line,
+                });
+            }
+            labels.insert(label.into_owned(), next_instr_index);
+        }
+        let slots = custom_op_slot_count(rest, custom_ops, options)
+            .unwrap_or(usize::from(is_instruction(rest)));
+        next_instr_index = next_instr_index
+            .checked_add(i64::try_from(slots).unwrap_or(i64::MAX))
+            .ok_or(AssemblerError::InvalidLine {
+                line: line.source_line,
+                reason: "warrior too long to index".to_owned(),
+            })?;
+    }
+
+    let mut instructions = Vec::new();
+    let mut start = None;
+    let mut pin = None;
+    let mut current_instr_index: i64 = 0;
+    for line in &expanded {
+        let (_, rest) = split_label(&line.text);
+        let rest = rest.trim();
+        if rest.is_empty() {
+            continue;
+        }
+
+        let resolve_relative = |name: &str| -> Option<i64> {
+            line.locals.get(name).copied().or_else(|| {
+                resolve_label(name, &line.locals, &labels)
+                    .map(|target| target - current_instr_index)
+            })
+        };
+        let resolve_absolute = |name: &str| -> Option<i64> {
+            line.locals
+                .get(name)
+                .copied()
+                .or_else(|| resolve_label(name, &line.locals, &labels))
+        };
+
+        if let Some((word, args)) = leading_word(rest) {
+            if let Some(handler) = custom_ops.get(word) {
+                let ctx = CustomOpContext {
+                    address: current_instr_index,
+                    resolve: &resolve_relative,
+                    options,
+                };
+                if let Some(outcome) = handler(args, &ctx) {
+                    match outcome {
+                        CustomOpOutcome::Instructions(new_instructions) => {
+                            let count = new_instructions.len();
+                            instructions.extend(new_instructions);
+                            current_instr_index = current_instr_index
+                                .checked_add(
+                                    i64::try_from(count).unwrap_or(i64::MAX),
+                                )
+                                .ok_or(AssemblerError::InvalidLine {
+                                    line: line.source_line,
+                                    reason: "warrior too long to index"
+                                        .to_owned(),
+                                })?;
+                        }
+                        CustomOpOutcome::Start(value) => start = Some(value),
+                        CustomOpOutcome::Pin(value) => pin = Some(value),
+                    }
+                    continue;
+                }
+            }
+        }
+
+        if let Some(expr) = strip_pseudo_op(rest, "ORG") {
+            start = Some(eval_expr(expr, line.source_line, resolve_absolute)?);
+        } else if let Some(expr) = strip_pseudo_op(rest, "END") {
+            if !expr.trim().is_empty() {
+                start =
+                    Some(eval_expr(expr, line.source_line, resolve_absolute)?);
+            }
+            break;
+        } else if let Some(expr) = strip_pseudo_op(rest, "PIN") {
+            pin = Some(eval_expr(expr, line.source_line, resolve_absolute)?);
+        } else {
+            instructions.push(parse_instruction(
+                rest,
+                options,
+                line.source_line,
+                resolve_relative,
+            )?);
+            current_instr_index = current_instr_index.checked_add(1).ok_or(
+                AssemblerError::InvalidLine {
+                    line: line.source_line,
+                    reason: "warrior too long to index".to_owned(),
+                },
+            )?;
+        }
+    }
+
+    Ok(RelaxedWarrior {
+        code: instructions,
+        start: start.unwrap_or(0),
+        pin,
+        name: None,
+        author: None,
+        strategy: Vec::new(),
+        metadata: BTreeMap::new(),
+    })
+}
+
+/// Returns the number of instruction slots a custom-op-matched `rest` would
+/// occupy, or `None` if no registered handler claims it.
+///
+/// Used during the first assembly pass to keep label offsets correct; the
+/// handler is invoked with a `resolve` that always returns `None`; a
+/// well-behaved handler's slot count depends only on the directive's
+/// arguments, not on resolved symbol values.
+fn custom_op_slot_count(
+    rest: &str,
+    custom_ops: &CustomOps,
+    options: ParseOptions,
+) -> Option<usize> {
+    let (word, args) = leading_word(rest.trim())?;
+    let handler = custom_ops.get(word)?;
+    let ctx = CustomOpContext {
+        address: 0,
+        resolve: &|_: &str| None,
+        options,
+    };
+    let outcome = handler(args, &ctx)?;
+    Some(match outcome {
+        CustomOpOutcome::Instructions(instrs) => instrs.len(),
+        CustomOpOutcome::Start(_) | CustomOpOutcome::Pin(_) => 0,
+    })
+}
+
+/// Splits `rest` into its leading identifier (a candidate directive name)
+/// and the remaining trailing text, if it begins with one.
+fn leading_word(rest: &str) -> Option<(&str, &str)> {
+    let end = rest
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .unwrap_or(rest.len());
+    if end == 0 {
+        None
+    } else {
+        Some((&rest[..end], rest[end..].trim_start()))
+    }
+}
+
+/// A line of source after `EQU` substitution and `FOR`/`ROF` expansion
+struct ExpandedLine {
+    /// The (possibly label-prefixed) line text, with original indentation
+    /// preserved so column-zero labels can still be distinguished
+    text: String,
+    /// The 1-based line number in the original source this came from
+    source_line: usize,
+    /// Values bound by enclosing `FOR` loop counters active for this line,
+    /// including the implicit `&` counter for the innermost loop
+    locals: BTreeMap<String, i64>,
+}
+
+/// Returns true if `rest` (the part of a line after any label) is an
+/// instruction rather than blank or a pseudo-op handled by the caller
+fn is_instruction(rest: &str) -> bool {
+    let rest = rest.trim();
+    !rest.is_empty()
+        && strip_pseudo_op(rest, "ORG").is_none()
+        && strip_pseudo_op(rest, "END").is_none()
+        && strip_pseudo_op(rest, "PIN").is_none()
+}
+
+/// If `rest` begins with the (case-insensitive) pseudo-op `keyword`, returns
+/// the trailing expression text
+fn strip_pseudo_op<'a>(rest: &'a str, keyword: &str) -> Option<&'a str> {
+    let bytes = rest.as_bytes();
+    if rest.len() < keyword.len()
+        || !rest[..keyword.len()].eq_ignore_ascii_case(keyword)
+    {
+        return None;
+    }
+    match bytes.get(keyword.len()) {
+        None => Some(""),
+        Some(c)
+            if c.is_ascii_whitespace()
+                || c.is_ascii_digit()
+                || matches!(c, b'+' | b'-' | b'(') =>
+        {
+            Some(rest[keyword.len()..].trim_start())
+        }
+        _ => None,
+    }
+}
+
+/// Resolves a trailing `&` in `label` against the innermost `FOR` loop
+/// counter active for its line, the standard convention for generating a
+/// label unique to each iteration (e.g. `lp&` becomes `lp1`, `lp2`, ...).
+///
+/// Returns `label` unchanged if it doesn't end in `&`, or if it does but no
+/// enclosing loop counter is in scope to concatenate.
+fn concatenate_ampersand<'a>(
+    label: &'a str,
+    locals: &BTreeMap<String, i64>,
+) -> Cow<'a, str> {
+    match (label.strip_suffix('&'), locals.get("&")) {
+        (Some(base), Some(counter)) => Cow::Owned(format!("{base}{counter}")),
+        _ => Cow::Borrowed(label),
+    }
+}
+
+/// Resolves an operand reference to a label: `name` itself if it's a key in
+/// `labels` directly, or, if `name` ends in `&`, the label generated for the
+/// current iteration by [`concatenate_ampersand`].
+fn resolve_label(
+    name: &str,
+    locals: &BTreeMap<String, i64>,
+    labels: &BTreeMap<String, i64>,
+) -> Option<i64> {
+    labels.get(concatenate_ampersand(name, locals).as_ref()).copied()
+}
+
+/// Splits a line into an optional column-zero label and the remaining text.
+///
+/// A label is a leading identifier with no preceding whitespace, unless that
+/// identifier is one of the pseudo-op keywords (`ORG`, `END`, `PIN`), which
+/// take their usual meaning even at column zero.
+fn split_label(line: &str) -> (Option<&str>, &str) {
+    if line.is_empty() || line.starts_with(char::is_whitespace) {
+        return (None, line);
+    }
+    let ident_end = line
+        .find(|c: char| c.is_whitespace())
+        .unwrap_or(line.len());
+    let candidate = &line[..ident_end];
+    if candidate.eq_ignore_ascii_case("ORG")
+        || candidate.eq_ignore_ascii_case("END")
+        || candidate.eq_ignore_ascii_case("PIN")
+    {
+        (None, line)
+    } else {
+        (Some(candidate), line[ident_end..].trim_start())
+    }
+}
+
+/// Performs `EQU` substitution as a left-to-right textual rewrite.
+///
+/// A line of the form `<identifier> EQU <rest>` (with no leading whitespace
+/// before the identifier) defines a substitution rather than emitting any
+/// instruction; its `rest` replaces every later whole-identifier occurrence
+/// of `<identifier>`, including in the definitions of later `EQU`s.
+fn substitute_equ(source: &str) -> String {
+    let mut equs: BTreeMap<String, String> = BTreeMap::new();
+    let mut out = String::new();
+    for line in source.lines() {
+        let substituted = apply_substitutions(line, &equs);
+        let (label, rest) = split_label(&substituted);
+        if let Some(label) = label {
+            if let Some(value) = strip_pseudo_op(rest, "EQU") {
+                equs.insert(label.to_owned(), value.to_owned());
+                out.push('\n');
+                continue;
+            }
+        }
+        out.push_str(&substituted);
+        out.push('\n');
+    }
+    out
+}
+
+/// Replaces every whole-identifier occurrence of a key in `equs` with its
+/// associated replacement text
+fn apply_substitutions(line: &str, equs: &BTreeMap<String, String>) -> String {
+    if equs.is_empty() {
+        return line.to_owned();
+    }
+    let mut out = String::with_capacity(line.len());
+    let mut chars = line.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        if c.is_ascii_alphabetic() || c == '_' {
+            let mut end = start + c.len_utf8();
+            while let Some(&(idx, next)) = chars.peek() {
+                if next.is_ascii_alphanumeric() || next == '_' {
+                    chars.next();
+                    end = idx + next.len_utf8();
+                } else {
+                    break;
+                }
+            }
+            let ident = &line[start..end];
+            match equs.get(ident) {
+                Some(replacement) => out.push_str(replacement),
+                None => out.push_str(ident),
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Returns `(loop_variable, count_expr)` if `line` opens a `FOR` block
+fn for_directive(line: &str) -> Option<(Option<&str>, &str)> {
+    let (label, rest) = split_label(line);
+    if let Some(expr) = strip_pseudo_op(rest, "FOR") {
+        return Some((label, expr));
+    }
+    if label.is_none() {
+        if let Some(expr) = strip_pseudo_op(line.trim_start(), "FOR") {
+            return Some((None, expr));
+        }
+    }
+    None
+}
+
+/// Returns true if `line` is a (possibly whitespace-padded) `ROF`
+fn is_rof(line: &str) -> bool {
+    line.trim().eq_ignore_ascii_case("ROF")
+}
+
+/// Expands every `FOR`/`ROF` block in `source`, which has already had its
+/// `EQU`s substituted.
+///
+/// # Errors
+///
+/// Returns an [`AssemblerError`] if a `FOR`/`ROF` pair is unbalanced, or if a
+/// loop count expression can't be evaluated.
+fn expand_for_rof(source: &str) -> Result<Vec<ExpandedLine>, AssemblerError> {
+    let lines: Vec<&str> = source.lines().collect();
+    let mut out = Vec::new();
+    let mut idx = 0;
+    expand_block(&lines, &mut idx, &BTreeMap::new(), &mut out)?;
+    if idx < lines.len() {
+        return Err(AssemblerError::UnmatchedRof {
+            line: idx.saturating_add(1),
+        });
+    }
+    Ok(out)
+}
+
+/// Expands a run of lines starting at `*idx`, stopping at a bare `ROF` (which
+/// is left unconsumed) or the end of `lines`
+fn expand_block(
+    lines: &[&str],
+    idx: &mut usize,
+    locals: &BTreeMap<String, i64>,
+    out: &mut Vec<ExpandedLine>,
+) -> Result<(), AssemblerError> {
+    while let Some(&line) = lines.get(*idx) {
+        if is_rof(line) {
+            return Ok(());
+        }
+        if let Some((loop_var, count_expr)) = for_directive(line) {
+            let for_line = idx.checked_add(1).unwrap_or(usize::MAX);
+            let count = eval_expr(count_expr, for_line, |name| {
+                locals.get(name).copied()
+            })?;
+            *idx = for_line;
+            let body_start = *idx;
+            let mut depth = 1_u32;
+            let mut scan = body_start;
+            while let Some(&body_line) = lines.get(scan) {
+                if for_directive(body_line).is_some() {
+                    depth = depth.saturating_add(1);
+                } else if is_rof(body_line) {
+                    depth = depth.saturating_sub(1);
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                scan = scan.saturating_add(1);
+            }
+            if depth != 0 {
+                return Err(AssemblerError::UnmatchedFor { line: for_line });
+            }
+            let count = count.max(0);
+            for i in 1..=count {
+                let mut iter_locals = locals.clone();
+                if let Some(loop_var) = loop_var {
+                    iter_locals.insert(loop_var.to_owned(), i);
+                }
+                // `&` always names the innermost enclosing loop's counter,
+                // regardless of whether that loop also bound a named
+                // variable; it's how a label or operand asks to be made
+                // unique per iteration (pMARS's `label&` convention).
+                iter_locals.insert("&".to_owned(), i);
+                // Recurse over the full, absolutely-indexed `lines` rather
+                // than a zero-based slice of the body, so `ExpandedLine`s
+                // pushed from inside the loop body report their real
+                // source line instead of one relative to the body's start.
+                let mut body_idx = body_start;
+                expand_block(lines, &mut body_idx, &iter_locals, out)?;
+            }
+            *idx = scan.saturating_add(1);
+        } else {
+            out.push(ExpandedLine {
+                text: (*line).to_owned(),
+                source_line: idx.saturating_add(1),
+                locals: locals.clone(),
+            });
+            *idx = idx.saturating_add(1);
+        }
+    }
+    Ok(())
+}
+
+/// Splits operand text at the first top-level comma (one not nested inside
+/// parentheses)
+fn split_operands(s: &str) -> Option<(&str, &str)> {
+    let mut depth = 0_i32;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth = depth.saturating_add(1),
+            ')' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => return Some((&s[..i], &s[i.saturating_add(1)..])),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Splits an operand into an optional leading address mode sigil and the
+/// remaining expression text, defaulting to [`AddrMode::Direct`] when no
+/// sigil is present
+fn extract_mode(operand: &str) -> (AddrMode, &str) {
+    match addr_mode(operand) {
+        Ok((rest, mode)) => (mode, rest),
+        Err(_) => (AddrMode::Direct, operand),
+    }
+}
+
+/// Parses one assembled instruction line (with any label already stripped)
+fn parse_instruction(
+    rest: &str,
+    options: ParseOptions,
+    source_line: usize,
+    resolve: impl Fn(&str) -> Option<i64>,
+) -> Result<RelaxedCompleteInstruction, AssemblerError> {
+    let invalid = |reason: &str| AssemblerError::InvalidLine {
+        line: source_line,
+        reason: reason.to_owned(),
+    };
+
+    let (after_op, op) =
+        opcode(rest).map_err(|_| invalid("expected an opcode"))?;
+    let (after_mod, explicit_modifier) =
+        match after_op.strip_prefix('.') {
+            Some(rest) => {
+                let (rest, m) = modifier(rest)
+                    .map_err(|_| invalid("expected a modifier after \".\""))?;
+                (rest, Some(m))
+            }
+            None => (after_op, None),
+        };
+    if explicit_modifier.is_none() && !options.omit_modifiers() {
+        return Err(invalid(
+            "expected a modifier; pass ParseOptions::ICWS_88_OPTIONS to \
+             omit modifiers",
+        ));
+    }
+
+    let operand_text = after_mod.trim_start();
+    let (a_text, b_text) = split_operands(operand_text)
+        .ok_or_else(|| invalid("expected two comma-separated operands"))?;
+    let (a_addr_mode, a_expr) = extract_mode(a_text.trim());
+    let (b_addr_mode, b_expr) = extract_mode(b_text.trim());
+
+    let modifier = explicit_modifier
+        .unwrap_or_else(|| default_modifiers(op, a_addr_mode, b_addr_mode));
+    let a_field = eval_expr(a_expr, source_line, &resolve)?;
+    let b_field = eval_expr(b_expr, source_line, &resolve)?;
+
+    Ok(RelaxedCompleteInstruction {
+        instr: Instruction {
+            opcode: op,
+            modifier,
+            a_addr_mode,
+            b_addr_mode,
+        },
+        a_field,
+        b_field,
+    })
+}
+
+/// Evaluates a `+ - * / %` arithmetic expression over integer literals,
+/// parenthesized groups, and symbols resolved by `resolve`.
+///
+/// # Errors
+///
+/// Returns an [`AssemblerError`] if the expression doesn't parse, references
+/// an undefined symbol, or divides or takes a remainder by zero.
+fn eval_expr(
+    text: &str,
+    source_line: usize,
+    resolve: impl Fn(&str) -> Option<i64>,
+) -> Result<i64, AssemblerError> {
+    let mut parser = ExprParser {
+        input: text,
+        source_line,
+        resolve: &resolve,
+    };
+    let value = parser.parse_sum()?;
+    if !parser.input.trim().is_empty() {
+        return Err(AssemblerError::InvalidExpression {
+            line: source_line,
+            text: text.to_owned(),
+        });
+    }
+    Ok(value)
+}
+
+/// Recursive-descent state for [`eval_expr`]
+struct ExprParser<'a, 'b> {
+    /// Remaining, unconsumed input
+    input: &'a str,
+    /// 1-based source line, carried along to build errors
+    source_line: usize,
+    /// Symbol resolver shared for the whole expression
+    resolve: &'b dyn Fn(&str) -> Option<i64>,
+}
+
+impl ExprParser<'_, '_> {
+    /// Parses `term (('+' | '-') term)*`
+    fn parse_sum(&mut self) -> Result<i64, AssemblerError> {
+        let mut value = self.parse_term()?;
+        loop {
+            self.input = self.input.trim_start();
+            match self.input.as_bytes().first() {
+                Some(b'+') => {
+                    self.input = &self.input[1..];
+                    value = value
+                        .checked_add(self.parse_term()?)
+                        .ok_or_else(|| self.invalid())?;
+                }
+                Some(b'-') => {
+                    self.input = &self.input[1..];
+                    value = value
+                        .checked_sub(self.parse_term()?)
+                        .ok_or_else(|| self.invalid())?;
+                }
+                _ => return Ok(value),
+            }
+        }
+    }
+
+    /// Parses `factor (('*' | '/' | '%') factor)*`
+    fn parse_term(&mut self) -> Result<i64, AssemblerError> {
+        let mut value = self.parse_factor()?;
+        loop {
+            self.input = self.input.trim_start();
+            match self.input.as_bytes().first() {
+                Some(b'*') => {
+                    self.input = &self.input[1..];
+                    value = value
+                        .checked_mul(self.parse_factor()?)
+                        .ok_or_else(|| self.invalid())?;
+                }
+                Some(b'/') => {
+                    self.input = &self.input[1..];
+                    let rhs = self.parse_factor()?;
+                    value =
+                        value.checked_div(rhs).ok_or_else(|| self.invalid())?;
+                }
+                Some(b'%') => {
+                    self.input = &self.input[1..];
+                    let rhs = self.parse_factor()?;
+                    value =
+                        value.checked_rem(rhs).ok_or_else(|| self.invalid())?;
+                }
+                _ => return Ok(value),
+            }
+        }
+    }
+
+    /// Parses a signed literal, symbol, or parenthesized sum
+    fn parse_factor(&mut self) -> Result<i64, AssemblerError> {
+        self.input = self.input.trim_start();
+        match self.input.as_bytes().first() {
+            Some(b'+') => {
+                self.input = &self.input[1..];
+                self.parse_factor()
+            }
+            Some(b'-') => {
+                self.input = &self.input[1..];
+                self.parse_factor()?.checked_neg().ok_or_else(|| self.invalid())
+            }
+            Some(b'(') => {
+                self.input = &self.input[1..];
+                let value = self.parse_sum()?;
+                self.input = self.input.trim_start();
+                if self.input.as_bytes().first() == Some(&b')') {
+                    self.input = &self.input[1..];
+                    Ok(value)
+                } else {
+                    Err(self.invalid())
+                }
+            }
+            Some(c) if c.is_ascii_digit() => {
+                let end = self
+                    .input
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(self.input.len());
+                let (digits, rest) = self.input.split_at(end);
+                self.input = rest;
+                digits.parse::<i64>().map_err(|_| self.invalid())
+            }
+            Some(c) if c.is_ascii_alphabetic() || *c == b'_' => {
+                let end = self
+                    .input
+                    .find(|c: char| {
+                        !(c.is_ascii_alphanumeric() || c == '_')
+                    })
+                    .unwrap_or(self.input.len());
+                // A trailing `&` asks for the label generated for the
+                // current FOR loop iteration rather than a literal label.
+                let end = if self.input[end..].starts_with('&') {
+                    end.saturating_add(1)
+                } else {
+                    end
+                };
+                let (ident, rest) = self.input.split_at(end);
+                self.input = rest;
+                (self.resolve)(ident).ok_or_else(|| {
+                    AssemblerError::UndefinedSymbol {
+                        name: ident.to_owned(),
+                        line: self.source_line,
+                    }
+                })
+            }
+            // `&` alone refers directly to the innermost `FOR` loop's
+            // counter, the same value a named loop variable would resolve
+            // to.
+            Some(b'&') => {
+                self.input = &self.input[1..];
+                (self.resolve)("&").ok_or_else(|| {
+                    AssemblerError::UndefinedSymbol {
+                        name: "&".to_owned(),
+                        line: self.source_line,
+                    }
+                })
+            }
+            _ => Err(self.invalid()),
+        }
+    }
+
+    /// Builds an [`AssemblerError::InvalidExpression`] carrying the input
+    /// still unconsumed at the point of failure, not the whole expression
+    fn invalid(&self) -> AssemblerError {
+        AssemblerError::InvalidExpression {
+            line: self.source_line,
+            text: self.input.to_owned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use redcode::{AddrMode, Modifier, Opcode};
+
+    use super::*;
+
+    #[test]
+    fn forward_label_reference() {
+        let source = "      MOV.I $0, target\ntarget DAT.F #0, #0\n";
+        let parsed =
+            parse_source(source, ParseOptions::default()).unwrap();
+        assert_eq!(parsed.code.len(), 2);
+        assert_eq!(parsed.code[0].instr.opcode, Opcode::Mov);
+        assert_eq!(parsed.code[0].a_field, 0);
+        assert_eq!(parsed.code[0].b_field, 1);
+    }
+
+    #[test]
+    fn backward_label_reference() {
+        let source = "start  DAT.F #0, #0\n       MOV.I $0, start\n";
+        let parsed =
+            parse_source(source, ParseOptions::default()).unwrap();
+        assert_eq!(parsed.code[1].b_field, -1);
+    }
+
+    #[test]
+    fn equ_textual_substitution() {
+        let source = "STEP EQU 4\n     MOV.I $0, #STEP\n";
+        let parsed =
+            parse_source(source, ParseOptions::default()).unwrap();
+        assert_eq!(parsed.code.len(), 1);
+        assert_eq!(parsed.code[0].b_field, 4);
+    }
+
+    #[test]
+    fn for_rof_expands_loop_counter() {
+        let source = "i FOR 3\n    DAT.F #i, #i\nROF\n";
+        let parsed =
+            parse_source(source, ParseOptions::default()).unwrap();
+        assert_eq!(parsed.code.len(), 3);
+        for (idx, instr) in parsed.code.iter().enumerate() {
+            let expected = i64::try_from(idx).unwrap() + 1;
+            assert_eq!(instr.a_field, expected);
+            assert_eq!(instr.b_field, expected);
+        }
+    }
+
+    #[test]
+    fn nested_for_rof() {
+        let source =
+            "i FOR 2\nj FOR 2\n    DAT.F #i, #j\nROF\nROF\n";
+        let parsed =
+            parse_source(source, ParseOptions::default()).unwrap();
+        let fields: Vec<(i64, i64)> = parsed
+            .code
+            .iter()
+            .map(|instr| (instr.a_field, instr.b_field))
+            .collect();
+        assert_eq!(fields, vec![(1, 1), (1, 2), (2, 1), (2, 2)]);
+    }
+
+    #[test]
+    fn unmatched_for_is_an_error() {
+        let source = "i FOR 3\n    DAT.F #0, #0\n";
+        let err = parse_source(source, ParseOptions::default()).unwrap_err();
+        assert!(matches!(err, AssemblerError::UnmatchedFor { .. }));
+    }
+
+    #[test]
+    fn unmatched_rof_is_an_error() {
+        let source = "    DAT.F #0, #0\nROF\n";
+        let err = parse_source(source, ParseOptions::default()).unwrap_err();
+        assert!(matches!(err, AssemblerError::UnmatchedRof { .. }));
+    }
+
+    #[test]
+    fn undefined_symbol_is_an_error() {
+        let source = "    MOV.I $0, nowhere\n";
+        let err = parse_source(source, ParseOptions::default()).unwrap_err();
+        assert!(matches!(err, AssemblerError::UndefinedSymbol { .. }));
+    }
+
+    #[test]
+    fn duplicate_label_is_an_error() {
+        let source = "lbl DAT.F #0, #0\nlbl DAT.F #0, #0\n";
+        let err = parse_source(source, ParseOptions::default()).unwrap_err();
+        match err {
+            AssemblerError::DuplicateLabel { name, line } => {
+                assert_eq!(name, "lbl");
+                assert_eq!(line, 2);
+            }
+            other => panic!("expected a DuplicateLabel error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn ampersand_generates_a_unique_label_per_loop_iteration() {
+        let source =
+            "i FOR 3\nlp& DAT.F #0, #0\nROF\n    MOV.I $0, lp3\n";
+        let parsed =
+            parse_source(source, ParseOptions::default()).unwrap();
+        assert_eq!(parsed.code.len(), 4);
+        // lp3 is the third generated label, at instruction index 2; the
+        // MOV referencing it sits at index 3.
+        assert_eq!(parsed.code[3].b_field, -1);
+    }
+
+    #[test]
+    fn bare_ampersand_resolves_to_the_loop_counter() {
+        let source = "    FOR 2\n    DAT.F #&, #&\nROF\n";
+        let parsed =
+            parse_source(source, ParseOptions::default()).unwrap();
+        assert_eq!(parsed.code.len(), 2);
+        assert_eq!(parsed.code[0].a_field, 1);
+        assert_eq!(parsed.code[1].a_field, 2);
+    }
+
+    #[test]
+    fn render_quotes_the_original_source_line() {
+        let source = "    JMP.A $0, #0\n    MOV.I $0, nowhere\n";
+        let err = parse_source(source, ParseOptions::default()).unwrap_err();
+        assert_eq!(err.line(), 2);
+        assert_eq!(
+            err.render(source),
+            format!("{err}\n    MOV.I $0, nowhere"),
+        );
+    }
+
+    #[test]
+    fn render_quotes_the_original_line_through_for_rof_expansion() {
+        let source = "i FOR 2\n    MOV.I $0, nowhere\nROF\n";
+        let err = parse_source(source, ParseOptions::default()).unwrap_err();
+        assert_eq!(err.line(), 2);
+        assert_eq!(
+            err.render(source),
+            format!("{err}\n    MOV.I $0, nowhere"),
+        );
+    }
+
+    #[test]
+    fn org_resolves_to_absolute_label_address() {
+        let source = "       JMP.A $0, #0\nstart  DAT.F #0, #0\n       ORG start\n";
+        let parsed =
+            parse_source(source, ParseOptions::default()).unwrap();
+        assert_eq!(parsed.start, 1);
+    }
+
+    #[test]
+    fn end_resolves_to_absolute_label_address() {
+        let source =
+            "       JMP.A $0, #0\nstart  DAT.F #0, #0\n       END start\n";
+        let parsed =
+            parse_source(source, ParseOptions::default()).unwrap();
+        assert_eq!(parsed.start, 1);
+    }
+
+    #[test]
+    fn expression_operators_and_parens() {
+        let source = "    DAT.F #(1 + 2) * 3, #10 % 3\n";
+        let parsed =
+            parse_source(source, ParseOptions::default()).unwrap();
+        assert_eq!(parsed.code[0].a_field, 9);
+        assert_eq!(parsed.code[0].b_field, 1);
+    }
+
+    #[test]
+    fn division_by_zero_is_an_error() {
+        let source = "    DAT.F #(1 / 0), #0\n";
+        let err = parse_source(source, ParseOptions::default()).unwrap_err();
+        assert!(matches!(err, AssemblerError::InvalidExpression { .. }));
+    }
+
+    #[test]
+    fn modulo_by_zero_is_an_error() {
+        let source = "    DAT.F #(1 % 0), #0\n";
+        let err = parse_source(source, ParseOptions::default()).unwrap_err();
+        assert!(matches!(err, AssemblerError::InvalidExpression { .. }));
+    }
+
+    #[test]
+    fn empty_parentheses_are_an_error() {
+        let source = "    DAT.F #(), #0\n";
+        let err = parse_source(source, ParseOptions::default()).unwrap_err();
+        assert!(matches!(err, AssemblerError::InvalidExpression { .. }));
+    }
+
+    #[test]
+    fn same_precedence_operators_are_left_associative() {
+        let source = "    DAT.F #10 - 3 - 2, #100 / 10 / 5\n";
+        let parsed =
+            parse_source(source, ParseOptions::default()).unwrap();
+        // Right-associative evaluation would give 10 - (3 - 2) = 9 and
+        // 100 / (10 / 5) = 50; left-associative gives 5 and 2.
+        assert_eq!(parsed.code[0].a_field, 5);
+        assert_eq!(parsed.code[0].b_field, 2);
+    }
+
+    #[test]
+    fn omitted_modifier_uses_icws_88_default() {
+        let source = "    ADD $0, #1\n";
+        let parsed =
+            parse_source(source, ParseOptions::ICWS_88_OPTIONS).unwrap();
+        assert_eq!(
+            parsed.code[0].instr.modifier,
+            default_modifiers(Opcode::Add, AddrMode::Direct, AddrMode::Immediate)
+        );
+    }
+
+    #[test]
+    fn modifier_required_without_icws_88_options() {
+        let source = "    ADD $0, #1\n";
+        let err =
+            parse_source(source, ParseOptions::default()).unwrap_err();
+        assert!(matches!(err, AssemblerError::InvalidLine { .. }));
+    }
+
+    #[test]
+    fn custom_op_can_append_instructions() {
+        let source = "      NOPSLED 2\ntarget DAT.F #0, #0\n";
+        let custom_ops = CustomOps::new().with_handler("NOPSLED", |args, _ctx| {
+            let count: usize = args.trim().parse().ok()?;
+            let nop = RelaxedCompleteInstruction {
+                instr: Instruction {
+                    opcode: Opcode::Nop,
+                    modifier: Modifier::F,
+                    a_addr_mode: AddrMode::Direct,
+                    b_addr_mode: AddrMode::Direct,
+                },
+                a_field: 0,
+                b_field: 0,
+            };
+            Some(CustomOpOutcome::Instructions(vec![nop; count]))
+        });
+        let parsed = parse_source_with_custom_ops(
+            source,
+            ParseOptions::default(),
+            &custom_ops,
+        )
+        .unwrap();
+        assert_eq!(parsed.code.len(), 3);
+        assert_eq!(parsed.code[0].instr.opcode, Opcode::Nop);
+        assert_eq!(parsed.code[1].instr.opcode, Opcode::Nop);
+        assert_eq!(parsed.code[2].instr.opcode, Opcode::Dat);
+        // The label should still resolve past the two inserted NOPs.
+        let referencing = "      MOV.I $0, target\n      NOPSLED 2\ntarget \
+                            DAT.F #0, #0\n";
+        let parsed = parse_source_with_custom_ops(
+            referencing,
+            ParseOptions::default(),
+            &custom_ops,
+        )
+        .unwrap();
+        assert_eq!(parsed.code[0].b_field, 3);
+    }
+
+    #[test]
+    fn custom_op_can_set_pin() {
+        let source = "    HOUSE_PIN 7\n    DAT.F #0, #0\n";
+        let custom_ops =
+            CustomOps::new().with_handler("HOUSE_PIN", |args, _ctx| {
+                args.trim().parse().ok().map(CustomOpOutcome::Pin)
+            });
+        let parsed = parse_source_with_custom_ops(
+            source,
+            ParseOptions::default(),
+            &custom_ops,
+        )
+        .unwrap();
+        assert_eq!(parsed.pin, Some(7));
+        assert_eq!(parsed.code.len(), 1);
+    }
+
+    #[test]
+    fn unregistered_directive_falls_through_to_an_error() {
+        let source = "    UNKNOWN_OP 1\n";
+        let err = parse_source_with_custom_ops(
+            source,
+            ParseOptions::default(),
+            &CustomOps::new(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, AssemblerError::InvalidLine { .. }));
+    }
+}
@@ -80,12 +80,17 @@
     clippy::needless_pass_by_value,
     reason = "Internal Compiler Error bug workaround: https://github.com/rust-lang/rust-clippy/issues/10344"
 )]
+// Drops the `std` prelude so the parsers are written against `core` and
+// `alloc`; `std` is still linked below. Left enabled under `cfg(test)` so
+// `cargo test` keeps using the ordinary std-backed test harness instead of
+// a custom no_std test runner.
+#![cfg_attr(not(test), no_std)]
 
 //! Parsers for CoreWar's redcode syntax.
 //!
 //! Currently, only loadfile parsing is supported.  Loadfiles may be parsed in
 //! either '88 or '94 format, and pMARS extension instructions and modifiers
-//! are supported.  
+//! are supported.
 //!
 //! For more information on the the syntax of the redcode language:
 //!  * The [94 ICWS Standard](https://corewar.co.uk/standards/icws94.htm)
@@ -95,11 +100,34 @@
 //! [`parser_grammar_specification`] contains the exact grammar.  The aim is to
 //! document the deviations from and interpretations of the standard.  The
 //! documents above are a better place to begin.
+//!
+//! `no_std` + `alloc` in its source: apart from
+//! [`format::ToLoadfile::write_loadfile`], which streams to an
+//! [`std::io::Write`], the crate uses only `core` and `alloc`. It still
+//! links `std` unconditionally for that one method, so unlike [`redcode`] it
+//! does not build on hosts with an allocator but no `std`.
+
+extern crate alloc;
+// Only [`format::ToLoadfile::write_loadfile`] needs this; a real `no_std`
+// build would gate it behind a Cargo `std` feature instead of linking it
+// unconditionally, but there's no manifest in this tree to add one to.
+extern crate std;
 
 /// Parsing functions for warriors and individual instructions either in '88 or
 /// '94 loadfile format
 pub mod loadfile_parser;
 
+/// A two-pass assembler that accepts source-level redcode: labels, `EQU`
+/// constants, and `FOR`/`ROF` loop expansion
+pub mod assembler;
+
+/// A canonical loadfile formatter, the inverse of [`loadfile_parser::parse`]
+pub mod format;
+
+/// A text-level preprocessing pass that expands `EQU` constants and
+/// `FOR`/`ROF` loops, gated behind [`loadfile_parser::ParseOptions::expand_macros`]
+pub mod preprocess;
+
 /// Internal functions which evaluate a the content of a single line, without
 /// consuming any newline characters
 mod line_parser;
@@ -3,7 +3,7 @@ use nom::{
     bytes::complete::{is_not, tag, tag_no_case},
     character::complete::space0,
     combinator::map,
-    error::VerboseError,
+    error::{context, VerboseError},
     sequence::{preceded, tuple},
     IResult,
 };
@@ -26,7 +26,7 @@ pub fn instr_94_line(
         space0,
         number,
         space0,
-        tag(","),
+        context("expected ',' separating the A-field and B-field", tag(",")),
         space0,
         addr_mode,
         space0,
@@ -81,7 +81,7 @@ pub fn instr_88_line(
         space0,
         number,
         space0,
-        tag(","),
+        context("expected ',' separating the A-field and B-field", tag(",")),
         space0,
         addr_mode,
         space0,
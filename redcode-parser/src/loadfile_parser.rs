@@ -1,7 +1,15 @@
+use alloc::{
+    borrow::ToOwned, collections::BTreeMap, format, string::String, vec,
+    vec::Vec,
+};
+use core::fmt;
+
 use nom::{
     branch::alt,
-    combinator::{eof, map},
-    error::VerboseError,
+    character::complete::satisfy,
+    combinator::{eof, map, recognize},
+    error::{VerboseError, VerboseErrorKind},
+    multi::many0_count,
     sequence::{pair, terminated},
     Err, IResult,
 };
@@ -12,7 +20,7 @@ use crate::{
         comment_line, empty_line, end_line, instr_88_line, instr_94_line,
         org_line, pin_line,
     },
-    loadfile_primitives::eol,
+    loadfile_primitives::{eol, modifier, opcode},
 };
 
 /// Configures parser behavior.
@@ -29,6 +37,10 @@ pub struct ParseOptions {
     disallow_empty_warrior: bool,
     /// Require parser to consume entire input
     must_consume_all: bool,
+    /// Lift pMARS-style `;key value` info comments into the parsed warrior
+    parse_metadata: bool,
+    /// Expand `EQU` constants and `FOR`/`ROF` loops before parsing
+    expand_macros: bool,
 }
 
 impl ParseOptions {
@@ -40,6 +52,8 @@ impl ParseOptions {
         omit_modifiers: false,
         disallow_empty_warrior: false,
         must_consume_all: false,
+        parse_metadata: false,
+        expand_macros: false,
     };
     /// Options for parsing an '88 loadfile.
     ///
@@ -51,6 +65,8 @@ impl ParseOptions {
         omit_modifiers: true,
         disallow_empty_warrior: false,
         must_consume_all: false,
+        parse_metadata: false,
+        expand_macros: false,
     };
     /// Default permissive options that parse '94 loadfiles.
     ///
@@ -65,6 +81,8 @@ impl ParseOptions {
         omit_modifiers: false,
         disallow_empty_warrior: true,
         must_consume_all: true,
+        parse_metadata: false,
+        expand_macros: false,
     };
 
     /// Require that modifiers be omitted from instructions, and use the default
@@ -99,6 +117,305 @@ impl ParseOptions {
         self.must_consume_all = true;
         self
     }
+
+    /// Lift pMARS-style info comments into structured warrior metadata.
+    ///
+    /// A comment whose body starts with an identifier immediately after the
+    /// `;` (e.g. `;name Imp`) is split into a lowercased key and the
+    /// remaining trailing text, rather than discarded.  `name` and `author`
+    /// populate [`RelaxedWarrior::name`] and [`RelaxedWarrior::author`],
+    /// `strategy` lines accumulate in [`RelaxedWarrior::strategy`], and any
+    /// other key accumulates into [`RelaxedWarrior::metadata`].  Comments
+    /// that don't match this shape are still discarded, as they are today,
+    /// and so is the `;redcode` header line (e.g. `;redcode-94`).
+    #[must_use]
+    pub const fn parse_metadata(mut self) -> Self {
+        self.parse_metadata = true;
+        self
+    }
+
+    /// Expand `EQU` symbolic constants and `FOR`/`ROF` loops before parsing.
+    ///
+    /// `EQU` definitions are substituted textually, with a label defined by
+    /// more than one `EQU` statement having its values appended together
+    /// (comma separated), matching ICWS 94's multi-line `EQU` convention.
+    /// `FOR <count> ... ROF` blocks are unrolled `count` times, with the
+    /// loop's label substituted by the current 1-based iteration number on
+    /// each copy. This is a purely textual pass; it has no notion of labels
+    /// or expressions beyond what [`parse`] already understands on its own,
+    /// unlike the full [`crate::assembler`]. Off by default, including in
+    /// [`ParseOptions::ICWS_88_OPTIONS`]. Setting it together with
+    /// [`ParseOptions::require_omitted_modifiers`] is not rejected: the
+    /// expansion then runs before the ICWS 88 grammar is applied.
+    #[must_use]
+    pub const fn expand_macros(mut self) -> Self {
+        self.expand_macros = true;
+        self
+    }
+
+    /// Whether modifiers should be omitted, in the style of ICWS 88.
+    pub(crate) const fn omit_modifiers(&self) -> bool {
+        self.omit_modifiers
+    }
+}
+
+/// A parse error located against the original source, for editor/LSP-style
+/// tooling where callers need a position rather than an unconsumed slice.
+///
+/// Internally, parsing is still driven by nom's [`VerboseError`]; this type
+/// is only constructed at the public API boundary, from the leftover slice
+/// nom reports and the original input it was given.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseError {
+    /// The 1-based line of the offending input
+    pub line: usize,
+    /// The 1-based column of the offending input
+    pub column: usize,
+    /// The full text of the offending source line, with no trailing newline
+    pub source_line: String,
+    /// A human readable description of what was expected
+    pub message: String,
+    /// Every alternative nom tried and failed at this position, most
+    /// specific first, as human readable descriptions. Usually starts with
+    /// the same text as `message`; tools that want to list every option
+    /// rather than just the first can use this instead.
+    pub expected: Vec<String>,
+    /// A coarse classification of the mistake, useful for tools that want to
+    /// react differently to e.g. a typo'd mnemonic versus a missing newline
+    pub kind: ParseErrorKind,
+    /// The closest known mnemonic to an unrecognized opcode or modifier, if
+    /// one was close enough to plausibly be a typo
+    pub suggestion: Option<String>,
+}
+
+/// A coarse classification of what went wrong at a [`ParseError`]'s position.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ParseErrorKind {
+    /// The text at this position isn't one of the known opcode mnemonics
+    UnknownOpcode,
+    /// The text following an instruction's `.` isn't one of the seven ICWS
+    /// 94 modifiers
+    IllegalModifier,
+    /// A valid instruction begins here, but wasn't preceded by a newline
+    /// separating it from the previous one
+    MissingNewline,
+    /// A numeric field (an operand, or an `ORG`/`END`/`PIN` argument)
+    /// couldn't be parsed
+    FieldOutOfRange,
+    /// None of the more specific categories applied
+    Other,
+}
+
+/// The valid ICWS 94 opcode mnemonics, used to suggest a correction for an
+/// unrecognized one
+const KNOWN_OPCODES: &[&str] = &[
+    "DAT", "MOV", "ADD", "SUB", "MUL", "DIV", "MOD", "JMP", "JMZ", "JMN",
+    "DJN", "SPL", "SLT", "CMP", "SEQ", "SNE", "NOP", "LDP", "STP",
+];
+
+/// The valid ICWS 94 modifier mnemonics, used to suggest a correction for an
+/// unrecognized one
+const KNOWN_MODIFIERS: &[&str] = &["A", "B", "AB", "BA", "F", "X", "I"];
+
+impl ParseError {
+    /// Builds a [`ParseError`] from the [`Err<VerboseError>`] nom produces
+    /// internally, resolving its reported position against `original_input`.
+    fn from_nom(original_input: &str, err: &Err<VerboseError<&str>>) -> Self {
+        let verbose = match err {
+            Err::Incomplete(_) => {
+                return Self {
+                    line: 1,
+                    column: 1,
+                    source_line: String::new(),
+                    message: "more input was needed to finish parsing"
+                        .to_owned(),
+                    expected: Vec::new(),
+                    kind: ParseErrorKind::Other,
+                    suggestion: None,
+                };
+            }
+            Err::Error(verbose) | Err::Failure(verbose) => verbose,
+        };
+        // `errors` accumulates one entry per combinator that failed or added
+        // context while unwinding, innermost first. Each entry is reported
+        // at the position where its own combinator started, so entries sit
+        // at different positions. The entry with the shortest leftover is
+        // the one that consumed the most input before giving up, which is
+        // the position worth reporting and classifying.
+        let Some((leftover, kind)) =
+            verbose.errors.iter().min_by_key(|(leftover, _)| leftover.len())
+        else {
+            return Self {
+                line: 1,
+                column: 1,
+                source_line: String::new(),
+                message: "parsing failed".to_owned(),
+                expected: Vec::new(),
+                kind: ParseErrorKind::Other,
+                suggestion: None,
+            };
+        };
+        let (line, column, source_line) = locate(original_input, leftover);
+        let preceding_byte = original_input
+            .as_bytes()
+            .get(
+                (leftover.as_ptr() as usize)
+                    .saturating_sub(original_input.as_ptr() as usize)
+                    .wrapping_sub(1),
+            )
+            .copied();
+        let (error_kind, suggestion) =
+            classify(leftover, preceding_byte == Some(b'.'));
+        let same_position: Vec<&VerboseErrorKind> = verbose
+            .errors
+            .iter()
+            .filter(|(candidate, _)| {
+                candidate.as_ptr() == leftover.as_ptr()
+                    && candidate.len() == leftover.len()
+            })
+            .map(|(_, kind)| kind)
+            .collect();
+        // A `context(...)` label at this position describes the failure in
+        // the grammar's own terms (e.g. "expected ',' separating the
+        // A-field and B-field"), which reads far better than the
+        // lowest-level combinator's generic message, so it wins when one is
+        // present.
+        let message = same_position
+            .iter()
+            .find(|kind| matches!(kind, VerboseErrorKind::Context(_)))
+            .map_or_else(|| describe_kind(kind), |kind| describe_kind(kind));
+        let expected: Vec<String> =
+            same_position.iter().map(|kind| describe_kind(kind)).collect();
+        Self {
+            line,
+            column,
+            source_line,
+            message,
+            expected,
+            kind: error_kind,
+            suggestion,
+        }
+    }
+}
+
+/// Classifies a parse failure at `leftover`, and suggests a correction for
+/// an unrecognized opcode or modifier mnemonic if one is close enough to
+/// plausibly be a typo.
+///
+/// `after_dot` is true when the character immediately preceding `leftover`
+/// in the source was a `.`, indicating the failure occurred while parsing a
+/// modifier rather than an opcode.
+fn classify(
+    leftover: &str,
+    after_dot: bool,
+) -> (ParseErrorKind, Option<String>) {
+    let token: String = leftover
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric())
+        .collect();
+
+    if !token.is_empty() && opcode(leftover).is_ok() {
+        return (ParseErrorKind::MissingNewline, None);
+    }
+    if after_dot {
+        if !token.is_empty() && modifier(leftover).is_ok() {
+            // A valid modifier parsed fine; the surrounding grammar failed
+            // for some other reason.
+            return (ParseErrorKind::Other, None);
+        }
+        let suggestion = closest_mnemonic(&token, KNOWN_MODIFIERS);
+        return (ParseErrorKind::IllegalModifier, suggestion);
+    }
+    if token.chars().next().is_some_and(|c| c.is_ascii_alphabetic()) {
+        let suggestion = closest_mnemonic(&token, KNOWN_OPCODES);
+        return (ParseErrorKind::UnknownOpcode, suggestion);
+    }
+    if leftover
+        .trim_start()
+        .starts_with(|c: char| c.is_ascii_digit() || c == '+' || c == '-')
+    {
+        return (ParseErrorKind::FieldOutOfRange, None);
+    }
+    (ParseErrorKind::Other, None)
+}
+
+/// Returns the entry in `candidates` closest to `token` by edit distance, if
+/// it's close enough (at most half of `token`'s length, and at least one) to
+/// plausibly be what the author meant.
+fn closest_mnemonic(token: &str, candidates: &[&str]) -> Option<String> {
+    if token.is_empty() {
+        return None;
+    }
+    let upper = token.to_uppercase();
+    let max_distance = (token.len() / 2).max(1);
+    candidates
+        .iter()
+        .map(|candidate| (*candidate, levenshtein(&upper, candidate)))
+        .filter(|&(_, distance)| distance > 0 && distance <= max_distance)
+        .min_by_key(|&(_, distance)| distance)
+        .map(|(candidate, _)| candidate.to_owned())
+}
+
+/// The classic dynamic-programming edit distance between two strings.
+fn levenshtein(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut previous_row: Vec<usize> = (0..=b.len()).collect();
+    for (i, &a_char) in a.iter().enumerate() {
+        let mut current_row = vec![i.saturating_add(1)];
+        for (j, &b_char) in b.iter().enumerate() {
+            let cost = usize::from(a_char != b_char);
+            let deletion = previous_row[j.saturating_add(1)].saturating_add(1);
+            let insertion = current_row[j].saturating_add(1);
+            let substitution = previous_row[j].saturating_add(cost);
+            current_row.push(deletion.min(insertion).min(substitution));
+        }
+        previous_row = current_row;
+    }
+    *previous_row.last().unwrap_or(&b.len())
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}:{}: {}", self.line, self.column, self.message)?;
+        writeln!(f, "{}", self.source_line)?;
+        write!(f, "{}^", " ".repeat(self.column.saturating_sub(1)))?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, " (did you mean \"{suggestion}\"?)")?;
+        }
+        Ok(())
+    }
+}
+
+impl core::error::Error for ParseError {}
+
+/// Resolves `leftover`, a suffix of `original_input` reported by nom, into a
+/// 1-based line and column and the full text of that source line.
+///
+/// The byte offset of `leftover` within `original_input` is computed via
+/// pointer arithmetic, since nom doesn't otherwise expose it.
+fn locate(original_input: &str, leftover: &str) -> (usize, usize, String) {
+    let offset = (leftover.as_ptr() as usize)
+        .saturating_sub(original_input.as_ptr() as usize)
+        .min(original_input.len());
+    let before = &original_input[..offset];
+    let line = before.matches('\n').count().saturating_add(1);
+    let line_start = before.rfind('\n').map_or(0, |i| i.saturating_add(1));
+    let column = offset.saturating_sub(line_start).saturating_add(1);
+    let line_end = original_input[offset..]
+        .find('\n')
+        .map_or(original_input.len(), |i| offset.saturating_add(i));
+    (line, column, original_input[line_start..line_end].to_owned())
+}
+
+/// Describes a single [`VerboseErrorKind`] entry in a short, human readable
+/// phrase
+fn describe_kind(kind: &VerboseErrorKind) -> String {
+    match kind {
+        VerboseErrorKind::Context(context) => (*context).to_owned(),
+        VerboseErrorKind::Char(expected) => format!("expected '{expected}'"),
+        VerboseErrorKind::Nom(kind) => format!("expected {kind:?}"),
+    }
 }
 
 /// Parse a loadfile formatted warrior from the input.
@@ -111,27 +428,54 @@ impl ParseOptions {
 ///
 /// # Errors
 ///
-/// Returns an error containing the source of the parsing issue and the
-/// unprocessed input if the content of the input doesn't match the redcode
-/// grammar.
+/// Returns a [`ParseError`] located against `warrior` if the content of the
+/// input doesn't match the redcode grammar.
 ///
 /// Also returns an error if any conditions specified by [`ParseOptions`] are
 /// violated.
+///
+/// When [`ParseOptions::expand_macros`] is set, `warrior` is first run
+/// through [`crate::preprocess::expand_macros`]. Any position reported by
+/// the grammar parser afterwards is located against the *expanded* source
+/// rather than `warrior` itself; an error from the expansion itself is
+/// located against the source after `EQU` substitution, with the `EQU`
+/// lines removed.
 pub fn parse(
     warrior: &str,
     options: ParseOptions,
-) -> Result<RelaxedWarrior, Err<VerboseError<&str>>> {
+) -> Result<RelaxedWarrior, ParseError> {
+    let expanded;
+    let warrior = if options.expand_macros {
+        expanded = crate::preprocess::expand_macros(warrior)?;
+        expanded.as_str()
+    } else {
+        warrior
+    };
     let mut input = warrior;
     let mut instructions = vec![];
     let mut start = None;
     let mut pin = None;
+    let mut name = None;
+    let mut author = None;
+    let mut strategy = vec![];
+    let mut metadata: BTreeMap<String, Vec<String>> = BTreeMap::new();
 
     loop {
-        match parse_line(input, options.omit_modifiers) {
+        match parse_line(input, options.omit_modifiers, options.parse_metadata)
+        {
             Ok((leftover, e)) => {
                 input = leftover;
                 match e {
                     LineContent::Empty() | LineContent::Comment(_) => {}
+                    LineContent::Meta { key, value } => match key.as_str() {
+                        "name" => name = Some(value.to_owned()),
+                        "author" => author = Some(value.to_owned()),
+                        "strategy" => strategy.push(value.to_owned()),
+                        _ => metadata
+                            .entry(key)
+                            .or_default()
+                            .push(value.to_owned()),
+                    },
                     LineContent::Pin(val) => pin = Some(val),
                     LineContent::Instruction(instr) => instructions.push(instr),
                     LineContent::Org(e) => start = Some(e),
@@ -142,7 +486,7 @@ pub fn parse(
                     LineContent::End(None) => break,
                 }
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(ParseError::from_nom(warrior, &e)),
         }
     }
     if options.must_consume_all {
@@ -150,30 +494,32 @@ pub fn parse(
         // whitespace including comments or additional
         // pseudo-instructions
         if !input.trim().is_empty() {
-            return Err(Err::Error(VerboseError {
+            let err = Err::Error(VerboseError {
                 errors: vec![(
                     input,
-                    nom::error::VerboseErrorKind::Context(
-                        "Expected end of input",
-                    ),
+                    VerboseErrorKind::Context("Expected end of input"),
                 )],
-            }));
+            });
+            return Err(ParseError::from_nom(warrior, &err));
         }
     }
     if options.disallow_empty_warrior && instructions.is_empty() {
-        return Err(Err::Error(VerboseError {
+        let err = Err::Error(VerboseError {
             errors: vec![(
                 input,
-                nom::error::VerboseErrorKind::Context(
-                    "Expected at least one instruction",
-                ),
+                VerboseErrorKind::Context("Expected at least one instruction"),
             )],
-        }));
+        });
+        return Err(ParseError::from_nom(warrior, &err));
     }
     Ok(RelaxedWarrior {
         code: instructions,
         start: start.unwrap_or(0),
         pin,
+        name,
+        author,
+        strategy,
+        metadata,
     })
 }
 
@@ -192,75 +538,389 @@ pub fn parse(
 ///
 /// # Errors
 ///
-/// Returns an error containing the source of the parsing issue and the
-/// unprocessed input if the content of the input does not contain a valid
-/// instruction, or if the first non-blank line couldn't be parsed as an
-/// instruction.
+/// Returns a [`ParseError`] located against `input` if the content of the
+/// input does not contain a valid instruction, or if the first non-blank
+/// line couldn't be parsed as an instruction.
 ///
 /// Also returns an error if any conditions specified by [`ParseOptions`] are
 /// violated.
 pub fn parse_instr(
     input: &str,
     options: ParseOptions,
-) -> Result<RelaxedCompleteInstruction, Err<VerboseError<&str>>> {
+) -> Result<RelaxedCompleteInstruction, ParseError> {
+    let original_input = input;
     let mut input = input;
 
     loop {
-        match parse_line(input, options.omit_modifiers) {
+        match parse_line(input, options.omit_modifiers, options.parse_metadata)
+        {
             Ok((leftovers, LineContent::Empty())) => {
                 input = leftovers;
             }
             Ok((_, LineContent::End(_))) => {
-                return Err(Err::Error(VerboseError {
+                let err = Err::Error(VerboseError {
                     errors: vec![(
                         input,
-                        nom::error::VerboseErrorKind::Context(
+                        VerboseErrorKind::Context(
                             "Unexpected end of input before instruction",
                         ),
                     )],
-                }));
+                });
+                return Err(ParseError::from_nom(original_input, &err));
             }
             Ok((
                 _,
                 LineContent::Comment(_)
+                | LineContent::Meta { .. }
                 | LineContent::Pin(_)
                 | LineContent::Org(_),
             )) => {
-                return Err(Err::Error(VerboseError {
+                let err = Err::Error(VerboseError {
                     errors: vec![(
                         input,
-                        nom::error::VerboseErrorKind::Context(
+                        VerboseErrorKind::Context(
                             "Unexpected redcode statement before instruction",
                         ),
                     )],
-                }));
+                });
+                return Err(ParseError::from_nom(original_input, &err));
             }
             Ok((leftovers, LineContent::Instruction(parsed_instruction))) => {
                 if options.must_consume_all && !leftovers.trim().is_empty() {
-                    return Err(Err::Error(VerboseError {
+                    let err = Err::Error(VerboseError {
                         errors: vec![(
                             leftovers,
-                            nom::error::VerboseErrorKind::Context(
+                            VerboseErrorKind::Context(
                                 "Unexpected content following an instruction, \
                                  disallowed by ParseOptions",
                             ),
                         )],
-                    }));
+                    });
+                    return Err(ParseError::from_nom(original_input, &err));
                 }
                 return Ok(parsed_instruction);
             }
-            Err(e) => return Err(e),
+            Err(e) => return Err(ParseError::from_nom(original_input, &e)),
+        }
+    }
+}
+
+/// Parses `warrior` like [`parse`], but recovers from a bad line instead of
+/// aborting on it: the error is recorded and parsing resumes at the next
+/// line, rather than returning on the first failure.
+///
+/// Returns the best-effort [`RelaxedWarrior`] assembled from every line that
+/// parsed successfully, alongside a [`ParseError`] for every line that
+/// didn't. An empty `Vec` means `warrior` parsed cleanly. This is meant for
+/// batch validation of many warriors, where reporting every problem in one
+/// pass is more useful than stopping at the first.
+#[must_use]
+pub fn parse_collecting(
+    warrior: &str,
+    options: ParseOptions,
+) -> (RelaxedWarrior, Vec<ParseError>) {
+    let mut input = warrior;
+    let mut instructions = vec![];
+    let mut start = None;
+    let mut pin = None;
+    let mut name = None;
+    let mut author = None;
+    let mut strategy = vec![];
+    let mut metadata: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut errors = vec![];
+
+    loop {
+        match parse_line(input, options.omit_modifiers, options.parse_metadata)
+        {
+            Ok((leftover, e)) => {
+                input = leftover;
+                match e {
+                    LineContent::Empty() | LineContent::Comment(_) => {}
+                    LineContent::Meta { key, value } => match key.as_str() {
+                        "name" => name = Some(value.to_owned()),
+                        "author" => author = Some(value.to_owned()),
+                        "strategy" => strategy.push(value.to_owned()),
+                        _ => metadata
+                            .entry(key)
+                            .or_default()
+                            .push(value.to_owned()),
+                    },
+                    LineContent::Pin(val) => pin = Some(val),
+                    LineContent::Instruction(instr) => instructions.push(instr),
+                    LineContent::Org(e) => start = Some(e),
+                    LineContent::End(Some(e)) => {
+                        start = Some(e);
+                        break;
+                    }
+                    LineContent::End(None) => break,
+                }
+            }
+            Err(e) => {
+                errors.push(ParseError::from_nom(warrior, &e));
+                match input.find('\n') {
+                    Some(idx) => input = &input[idx.saturating_add(1)..],
+                    None => break,
+                }
+            }
+        }
+    }
+    if options.must_consume_all && !input.trim().is_empty() {
+        let err = Err::Error(VerboseError {
+            errors: vec![(
+                input,
+                VerboseErrorKind::Context("Expected end of input"),
+            )],
+        });
+        errors.push(ParseError::from_nom(warrior, &err));
+    }
+    if options.disallow_empty_warrior && instructions.is_empty() {
+        let err = Err::Error(VerboseError {
+            errors: vec![(
+                input,
+                VerboseErrorKind::Context("Expected at least one instruction"),
+            )],
+        });
+        errors.push(ParseError::from_nom(warrior, &err));
+    }
+    let warrior = RelaxedWarrior {
+        code: instructions,
+        start: start.unwrap_or(0),
+        pin,
+        name,
+        author,
+        strategy,
+        metadata,
+    };
+    (warrior, errors)
+}
+
+/// The result of [`parse_incremental`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Incremental {
+    /// The parser reached an `END` statement, or ran out of input with no
+    /// trailing fragment that could still become a valid instruction.
+    Complete {
+        /// The warrior assembled from every line consumed so far
+        warrior: RelaxedWarrior,
+        /// The number of bytes of the input that contributed to `warrior`
+        consumed: usize,
+    },
+    /// The parser consumed every complete line it could, but the remaining
+    /// bytes are a prefix that could still become a valid instruction once
+    /// more input arrives (e.g. a bare opcode mnemonic with no operands
+    /// yet). Callers should buffer more bytes and call
+    /// [`parse_incremental`] again with the combined input.
+    Incomplete {
+        /// The warrior assembled from every complete line consumed so far
+        warrior: RelaxedWarrior,
+        /// The number of bytes of the input that contributed to `warrior`
+        consumed: usize,
+    },
+}
+
+/// Parses as much of `warrior` as it can, for callers that receive source
+/// incrementally (e.g. over a network connection, or as a user types in an
+/// editor).
+///
+/// Returns [`Incremental::Incomplete`] rather than a [`ParseError`] when the
+/// unconsumed trailing fragment could still become a valid instruction as
+/// more bytes arrive, so callers can distinguish "malformed" from "valid but
+/// truncated" and buffer more input before re-invoking. Like [`parse`],
+/// this never fails merely because the input doesn't end in an `END`
+/// statement. A last line with no trailing newline that already parses on
+/// its own (e.g. `DAT.F #3, #4`) is consumed and yields
+/// [`Incremental::Complete`], even though more bytes could still extend it.
+///
+/// # Errors
+///
+/// Returns a [`ParseError`] if a complete line of input doesn't match the
+/// redcode grammar.
+pub fn parse_incremental(
+    warrior: &str,
+    options: ParseOptions,
+) -> Result<Incremental, ParseError> {
+    let mut input = warrior;
+    let mut instructions = vec![];
+    let mut start = None;
+    let mut pin = None;
+    let mut name = None;
+    let mut author = None;
+    let mut strategy = vec![];
+    let mut metadata: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut complete = false;
+
+    loop {
+        match parse_line(input, options.omit_modifiers, options.parse_metadata)
+        {
+            Ok((leftover, e)) => {
+                input = leftover;
+                match e {
+                    LineContent::Empty() | LineContent::Comment(_) => {}
+                    LineContent::Meta { key, value } => match key.as_str() {
+                        "name" => name = Some(value.to_owned()),
+                        "author" => author = Some(value.to_owned()),
+                        "strategy" => strategy.push(value.to_owned()),
+                        _ => metadata
+                            .entry(key)
+                            .or_default()
+                            .push(value.to_owned()),
+                    },
+                    LineContent::Pin(val) => pin = Some(val),
+                    LineContent::Instruction(instr) => instructions.push(instr),
+                    LineContent::Org(e) => start = Some(e),
+                    LineContent::End(Some(e)) => {
+                        start = Some(e);
+                        complete = true;
+                        break;
+                    }
+                    LineContent::End(None) => {
+                        complete = true;
+                        break;
+                    }
+                }
+            }
+            Err(ref e) if looks_incomplete(input, e) => break,
+            Err(e) => return Err(ParseError::from_nom(warrior, &e)),
         }
     }
+
+    let consumed = warrior.len().saturating_sub(input.len());
+    let warrior = RelaxedWarrior {
+        code: instructions,
+        start: start.unwrap_or(0),
+        pin,
+        name,
+        author,
+        strategy,
+        metadata,
+    };
+    Ok(if complete {
+        Incremental::Complete { warrior, consumed }
+    } else {
+        Incremental::Incomplete { warrior, consumed }
+    })
+}
+
+/// Returns true if a failed parse of `fragment` (which produced `err`)
+/// should be treated as "not enough input yet" rather than a malformed
+/// line.
+///
+/// Two situations qualify: `fragment` is itself a bare mnemonic (optionally
+/// followed by a `.` and the start of a modifier) that is a prefix of a
+/// [`KNOWN_OPCODES`] entry, with no operand tokens started yet (e.g. "MO" or
+/// "DAT."); or the innermost nom error in `err` is reported against the
+/// empty string, meaning every combinator that failed ran out of bytes to
+/// match against rather than rejecting a byte it actually saw (e.g.
+/// "DAT.F #1" with no comma and B-field yet). Either way, the fragment could
+/// still become a valid instruction once more bytes arrive.
+fn looks_incomplete(fragment: &str, err: &Err<VerboseError<&str>>) -> bool {
+    let trimmed = fragment.trim();
+    if trimmed.is_empty() {
+        return true;
+    }
+    if !trimmed.contains(char::is_whitespace) {
+        let mnemonic = trimmed.split('.').next().unwrap_or(trimmed);
+        let modifier_prefix_ok = trimmed.len() == mnemonic.len()
+            || trimmed[mnemonic.len().saturating_add(1)..]
+                .chars()
+                .all(|c| c.is_ascii_alphabetic());
+        if modifier_prefix_ok
+            && mnemonic.chars().all(|c| c.is_ascii_alphabetic())
+            && KNOWN_OPCODES.iter().any(|op| {
+                op.len() >= mnemonic.len()
+                    && op[..mnemonic.len()].eq_ignore_ascii_case(mnemonic)
+            })
+        {
+            return true;
+        }
+    }
+    let verbose = match err {
+        Err::Incomplete(_) => return true,
+        Err::Error(verbose) | Err::Failure(verbose) => verbose,
+    };
+    // As in `ParseError::from_nom`, entries sit at different positions; the
+    // entry with the shortest leftover is the deepest failure, the one that
+    // would have run out of bytes.
+    verbose
+        .errors
+        .iter()
+        .min_by_key(|(leftover, _)| leftover.len())
+        .is_some_and(|(leftover, _)| leftover.trim().is_empty())
+}
+
+/// Accumulates chunks of loadfile text received over time (e.g. from a
+/// socket or a REPL) and hands back newly parsed instructions as soon as
+/// enough bytes have arrived to complete them.
+///
+/// This is a thin stateful wrapper around [`parse_incremental`]: it owns the
+/// growing buffer and remembers how many instructions it has already
+/// returned, so a caller reading from e.g. a `Read` implementor doesn't have
+/// to re-derive that bookkeeping itself at every call site.
+#[derive(Clone, Debug)]
+pub struct StreamingLoader {
+    options: ParseOptions,
+    buffer: String,
+    yielded: usize,
+}
+
+impl StreamingLoader {
+    /// Creates an empty loader that will parse fed chunks as `options`
+    /// directs.
+    #[must_use]
+    pub fn new(options: ParseOptions) -> Self {
+        Self {
+            options,
+            buffer: String::new(),
+            yielded: 0,
+        }
+    }
+
+    /// Appends `chunk` to the buffered input and returns every instruction
+    /// that has newly become complete as a result, in source order.
+    ///
+    /// Instructions already returned by a previous call aren't repeated.
+    /// Once an `END` line has been fed the loader keeps returning an empty
+    /// slice for any further chunks fed to it, since a loadfile has only one
+    /// `END`. Input that merely ends cleanly without an `END` does not stop
+    /// the loader: instructions in later chunks are still returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ParseError`] if the buffered input so far contains a line
+    /// that doesn't match the redcode grammar.
+    pub fn feed(
+        &mut self,
+        chunk: &str,
+    ) -> Result<Vec<RelaxedCompleteInstruction>, ParseError> {
+        self.buffer.push_str(chunk);
+        let warrior = match parse_incremental(&self.buffer, self.options)? {
+            Incremental::Complete { warrior, .. }
+            | Incremental::Incomplete { warrior, .. } => warrior,
+        };
+        let new_instructions = warrior.code[self.yielded..].to_vec();
+        self.yielded = warrior.code.len();
+        Ok(new_instructions)
+    }
 }
 
 /// A container for the parsed contents a bit of the input, either terminated by
 /// an EOL, an EOF, or an END line (which itself may be terminated by EOF or
 /// EOL)
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 enum LineContent<'a> {
     /// Contains the text parsed from a comment
     Comment(&'a str),
+    /// Contains a metadata comment, recognized when a `;` is immediately
+    /// followed by an identifier other than `redcode` (e.g. `;name Imp`).
+    /// `key` is the
+    /// lowercased identifier, and `value` is the trailing text with any
+    /// leading whitespace trimmed.
+    Meta {
+        /// The lowercased identifier following the `;`
+        key: String,
+        /// The trailing text after the key
+        value: &'a str,
+    },
     /// Contains a parsed instruction from the input
     Instruction(RelaxedCompleteInstruction),
     /// Represents a line that was parsed but contained only whitespace
@@ -282,6 +942,7 @@ enum LineContent<'a> {
 fn parse_line(
     input: &str,
     omit_modifier: bool,
+    parse_metadata: bool,
 ) -> IResult<&str, LineContent, VerboseError<&str>> {
     let parse_instr = if omit_modifier {
         instr_88_line
@@ -289,13 +950,25 @@ fn parse_line(
         instr_94_line
     };
 
+    let comment_content_parser = map(comment_line, move |text| {
+        let meta = if parse_metadata {
+            split_metadata_comment(text)
+        } else {
+            None
+        };
+        match meta {
+            Some((key, value)) => LineContent::Meta { key, value },
+            None => LineContent::Comment(text),
+        }
+    });
+
     // Parse the content from an eol or eof terminated segment of input
     // If terminated by EOF, we return the content, and the next invocation
     // will not match any body_content parsers, but will match an
     // end_content_parser
     let body_content_parser = alt((
         map(parse_instr, LineContent::Instruction),
-        map(comment_line, LineContent::Comment),
+        comment_content_parser,
         map(org_line, LineContent::Org),
         map(pin_line, LineContent::Pin),
         map(empty_line, |_| LineContent::Empty()),
@@ -316,6 +989,27 @@ fn parse_line(
     alt((end_content_parser, body_content_parser))(input)
 }
 
+/// Splits a comment body into a metadata key and value, if it matches the
+/// pMARS info comment shape of `;` immediately followed by an identifier
+/// (e.g. `;name Imp`).  The key is lowercased, and the value has any leading
+/// whitespace trimmed.  Returns `None` if the comment doesn't begin with an
+/// identifier character immediately after the `;`, or if that identifier is
+/// `redcode` (the `;redcode-94` header line), which is discarded like an
+/// ordinary comment.
+fn split_metadata_comment(comment: &str) -> Option<(String, &str)> {
+    let mut ident = recognize(pair(
+        satisfy(|c: char| c.is_ascii_alphabetic() || c == '_'),
+        many0_count(satisfy(|c: char| c.is_ascii_alphanumeric() || c == '_')),
+    ));
+    let parsed: IResult<&str, &str, VerboseError<&str>> = ident(comment);
+    let (rest, key) = parsed.ok()?;
+    let key = key.to_lowercase();
+    if key == "redcode" {
+        return None;
+    }
+    Some((key, rest.trim_start()))
+}
+
 #[cfg(test)]
 mod tests {
     use redcode::*;
@@ -487,6 +1181,7 @@ mod tests {
                 ],
                 start: 0,
                 pin: None,
+                ..Default::default()
             })
         );
     }
@@ -515,6 +1210,7 @@ mod tests {
                     }],
                     start: 0,
                     pin: None,
+                    ..Default::default()
                 }),
                 "Failed to parse warrior: {input}"
             );
@@ -651,6 +1347,7 @@ mod tests {
                     }],
                     start: 0,
                     pin: None,
+                    ..Default::default()
                 },
             ),
             (
@@ -708,6 +1405,7 @@ mod tests {
                     ],
                     start: 177,
                     pin: Some(13),
+                    ..Default::default()
                 },
             ),
             (
@@ -716,6 +1414,7 @@ mod tests {
                     code: vec![],
                     start: 0,
                     pin: None,
+                    ..Default::default()
                 },
             ),
         ];
@@ -792,4 +1491,255 @@ mod tests {
             assert_eq!(parsed.unwrap().start, correct_start, "{desc}");
         }
     }
+
+    #[test]
+    fn parse_warrior_metadata() {
+        let warrior = ";redcode-94
+                          ;name Imp
+                          ;author A. K. Dewdney
+                          ;strategy Moves by one instruction each cycle.
+                          ;strategy Outruns anything that isn't an Imp.
+                          ;assert 1
+                          MOV.I $0, $1
+                          END";
+        let parsed =
+            parse(warrior, ParseOptions::default().parse_metadata()).unwrap();
+        assert_eq!(parsed.name, Some("Imp".to_owned()));
+        assert_eq!(parsed.author, Some("A. K. Dewdney".to_owned()));
+        assert_eq!(
+            parsed.strategy,
+            vec![
+                "Moves by one instruction each cycle.".to_owned(),
+                "Outruns anything that isn't an Imp.".to_owned(),
+            ]
+        );
+        assert_eq!(
+            parsed.metadata.get("assert"),
+            Some(&vec!["1".to_owned()])
+        );
+        assert!(parsed.metadata.get("name").is_none());
+    }
+
+    #[test]
+    fn parse_warrior_without_metadata_flag_discards_comments() {
+        let warrior = ";name Imp\nMOV.I $0, $1";
+        let parsed = parse(warrior, ParseOptions::default()).unwrap();
+        assert_eq!(parsed.name, None);
+        assert!(parsed.metadata.is_empty());
+    }
+
+    #[test]
+    fn parse_error_locates_the_offending_line_and_column() {
+        let warrior = "DAT.AB #1, $2\nnonsense\nDAT.F #3, #4";
+        let err = parse(warrior, ParseOptions::default()).unwrap_err();
+        assert_eq!(err.line, 2);
+        assert_eq!(err.column, 1);
+        assert_eq!(err.source_line, "nonsense");
+    }
+
+    #[test]
+    fn parse_error_lists_every_alternative_expected_at_the_position() {
+        let warrior = "DAT.AB #1, $2\nnonsense\nDAT.F #3, #4";
+        let err = parse(warrior, ParseOptions::default()).unwrap_err();
+        assert!(!err.expected.is_empty());
+        assert!(err.expected.contains(&err.message));
+    }
+
+    #[test]
+    fn parse_error_names_a_missing_comma_using_its_context_label() {
+        let warrior = "DAT.F #1 $2";
+        let err = parse(warrior, ParseOptions::default()).unwrap_err();
+        assert_eq!(
+            err.message,
+            "expected ',' separating the A-field and B-field"
+        );
+    }
+
+    #[test]
+    fn parse_error_display_renders_a_caret_under_the_column() {
+        let warrior = "  nonsense";
+        let err = parse(warrior, ParseOptions::default()).unwrap_err();
+        let rendered = err.to_string();
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines.get(1), Some(&"  nonsense"));
+        let caret_line = lines.get(2).expect("caret line");
+        assert_eq!(caret_line.trim_end(), " ".repeat(err.column - 1) + "^");
+    }
+
+    #[test]
+    fn parse_collecting_reports_every_bad_line_in_one_pass() {
+        let warrior =
+            "DAT.AB #1, $2\nnonsense\nDAT.F #3, #4\nmore nonsense\n";
+        let (parsed, errors) =
+            parse_collecting(warrior, ParseOptions::default());
+        assert_eq!(parsed.code.len(), 2);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].line, 2);
+        assert_eq!(errors[1].line, 4);
+    }
+
+    #[test]
+    fn parse_collecting_returns_no_errors_for_clean_input() {
+        let warrior = "DAT.AB #1, $2\nDAT.F #3, #4\n";
+        let (parsed, errors) =
+            parse_collecting(warrior, ParseOptions::default());
+        assert_eq!(parsed.code.len(), 2);
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn unknown_opcode_suggests_the_closest_valid_mnemonic() {
+        let warrior = "MOF.I $0, $1\n";
+        let err = parse(warrior, ParseOptions::default()).unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::UnknownOpcode);
+        assert_eq!(err.suggestion, Some("MOV".to_owned()));
+    }
+
+    #[test]
+    fn illegal_modifier_is_classified_and_suggested() {
+        let warrior = "DAT.Z $0, $1\n";
+        let err = parse(warrior, ParseOptions::default()).unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::IllegalModifier);
+    }
+
+    #[test]
+    fn missing_newline_between_instructions_is_classified() {
+        let warrior = "DAT.AB #1, #2DAT.F #3, #4";
+        let err = parse(warrior, ParseOptions::default()).unwrap_err();
+        assert_eq!(err.kind, ParseErrorKind::MissingNewline);
+    }
+
+    #[test]
+    fn expand_macros_expands_equ_and_for_rof_before_parsing() {
+        let warrior = "step EQU 1\ni FOR 3\nDAT.F #step, #i\nROF\n";
+        let parsed =
+            parse(warrior, ParseOptions::default().expand_macros()).unwrap();
+        assert_eq!(parsed.code.len(), 3);
+        assert_eq!(parsed.code[0].a_field, 1);
+        assert_eq!(parsed.code[0].b_field, 1);
+        assert_eq!(parsed.code[2].b_field, 3);
+    }
+
+    #[test]
+    fn expand_macros_is_not_applied_without_the_option() {
+        let warrior = "i FOR 3\nDAT.F #i, #i\nROF\n";
+        let parsed = parse(warrior, ParseOptions::default());
+        assert!(
+            parsed.is_err(),
+            "FOR/ROF should not be expanded unless expand_macros is set"
+        );
+    }
+
+    #[test]
+    fn parse_incremental_reports_incomplete_for_a_bare_mnemonic() {
+        let input = "DAT.AB #1, $2\nMOV";
+        let result =
+            parse_incremental(input, ParseOptions::default()).unwrap();
+        match result {
+            Incremental::Incomplete { warrior, consumed } => {
+                assert_eq!(warrior.code.len(), 1);
+                assert_eq!(consumed, "DAT.AB #1, $2\n".len());
+            }
+            Incremental::Complete { .. } => {
+                panic!("expected an incomplete result")
+            }
+        }
+    }
+
+    #[test]
+    fn parse_incremental_reports_complete_once_input_ends_cleanly() {
+        let input = "DAT.AB #1, $2\nDAT.F #3, #4\n";
+        let result =
+            parse_incremental(input, ParseOptions::default()).unwrap();
+        match result {
+            Incremental::Complete { warrior, consumed } => {
+                assert_eq!(warrior.code.len(), 2);
+                assert_eq!(consumed, input.len());
+            }
+            Incremental::Incomplete { .. } => {
+                panic!("expected a complete result")
+            }
+        }
+    }
+
+    #[test]
+    fn parse_incremental_reports_incomplete_mid_instruction() {
+        let input = "DAT.AB #1, $2\nMOV.I #1";
+        let result =
+            parse_incremental(input, ParseOptions::default()).unwrap();
+        match result {
+            Incremental::Incomplete { warrior, consumed } => {
+                assert_eq!(warrior.code.len(), 1);
+                assert_eq!(consumed, "DAT.AB #1, $2\n".len());
+            }
+            Incremental::Complete { .. } => {
+                panic!("expected an incomplete result")
+            }
+        }
+    }
+
+    #[test]
+    fn parse_incremental_still_errors_on_malformed_input() {
+        let input = "nonsense";
+        let result = parse_incremental(input, ParseOptions::default());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn streaming_loader_yields_each_instruction_only_once() {
+        let mut loader = StreamingLoader::new(ParseOptions::default());
+        assert_eq!(loader.feed("DAT.AB #1, $2\nMOV").unwrap().len(), 1);
+        assert_eq!(loader.feed(".I #3").unwrap().len(), 0);
+        let last = loader.feed(", #4\n").unwrap();
+        assert_eq!(last.len(), 1);
+        assert_eq!(last[0].a_field, 3);
+    }
+
+    #[test]
+    fn streaming_loader_surfaces_malformed_lines_as_an_error() {
+        let mut loader = StreamingLoader::new(ParseOptions::default());
+        assert!(loader.feed("nonsense\n").is_err());
+    }
+
+    /// A tiny deterministic PRNG, so the fuzz-style test below is
+    /// reproducible without pulling in `proptest` or `rand`.
+    struct Lcg(u64);
+
+    impl Lcg {
+        /// Knuth's MMIX LCG constants.
+        fn next_u64(&mut self) -> u64 {
+            self.0 = self
+                .0
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            self.0
+        }
+    }
+
+    /// Builds an arbitrary chunk of loadfile-ish text: mnemonics, sigils,
+    /// numbers, pseudo-ops, and comment/EQU/FOR/ROF keywords, so the fuzz
+    /// test below exercises `parse`'s line-to-line looping, not just a
+    /// single primitive.
+    fn arbitrary_loadfile_text(rng: &mut Lcg) -> String {
+        const TOKENS: &[&str] = &[
+            "DAT", "MOV", "JMP", "ADD", ".", ",", "#", "$", "*", "@", "{",
+            "}", "<", ">", "0", "1", "9", "+", "-", "x", " ", "\n", "\r",
+            ";", "ORG", "PIN", "END", "EQU", "FOR", "ROF",
+        ];
+        let len = (rng.next_u64() % 40) as usize;
+        (0..len)
+            .map(|_| TOKENS[(rng.next_u64() as usize) % TOKENS.len()])
+            .collect()
+    }
+
+    #[test]
+    fn fuzz_parse_never_panics_on_arbitrary_input() {
+        let mut rng = Lcg(0xDEAD_BEEF_CAFE_F00D);
+        for _ in 0..2_000 {
+            let input = arbitrary_loadfile_text(&mut rng);
+            let _ = parse(&input, ParseOptions::default());
+            let _ = parse(&input, ParseOptions::default().expand_macros());
+            let _ = parse(&input, ParseOptions::ICWS_88_OPTIONS);
+        }
+    }
 }
@@ -218,4 +218,56 @@ mod tests {
             assert!(number(input).is_err(), "{}: input: {}", desc, input);
         }
     }
+
+    /// A tiny deterministic PRNG, so the fuzz-style test below is
+    /// reproducible without pulling in `proptest` or `rand`.
+    struct Lcg(u64);
+
+    impl Lcg {
+        /// Knuth's MMIX LCG constants.
+        fn next_u64(&mut self) -> u64 {
+            self.0 = self
+                .0
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            self.0
+        }
+    }
+
+    /// Builds an arbitrary short string out of characters meaningful to
+    /// `only_number`/`opcode`/`addr_mode`/`modifier`'s grammars, plus a few
+    /// that aren't, biased toward the sign-prefix and hex-prefix edge cases
+    /// these parsers are hand-rolled around.
+    fn arbitrary_string(rng: &mut Lcg) -> String {
+        const ALPHABET: &[u8] =
+            b"0123456789+-.,MOVDATJNPSLECIXABFxob #$*@{}<> \n";
+        let len = (rng.next_u64() % 9) as usize;
+        (0..len)
+            .map(|_| {
+                let i = (rng.next_u64() as usize) % ALPHABET.len();
+                ALPHABET[i] as char
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fuzz_primitives_never_panic_and_never_grow_their_input() {
+        let mut rng = Lcg(0x2545_F491_4F6C_DD1D);
+        for _ in 0..10_000 {
+            let input = arbitrary_string(&mut rng);
+            for result_len in [
+                number(&input).ok().map(|(leftover, _)| leftover.len()),
+                opcode(&input).ok().map(|(leftover, _)| leftover.len()),
+                addr_mode(&input).ok().map(|(leftover, _)| leftover.len()),
+                modifier(&input).ok().map(|(leftover, _)| leftover.len()),
+            ] {
+                if let Some(leftover_len) = result_len {
+                    assert!(
+                        leftover_len <= input.len(),
+                        "parser grew its input: {input:?}"
+                    );
+                }
+            }
+        }
+    }
 }
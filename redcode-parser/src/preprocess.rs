@@ -0,0 +1,308 @@
+//! A text-level preprocessing pass that expands `EQU` constants and
+//! `FOR`/`ROF` loops before the result reaches [`crate::loadfile_parser`].
+//!
+//! Unlike [`crate::assembler`], which assembles full source with labels and
+//! expressions into a [`redcode::RelaxedWarrior`] directly, this pass only
+//! ever produces plain loadfile text: every substitution is textual, so its
+//! output can be fed straight into the unmodified
+//! [`crate::loadfile_parser::parse`]. [`ParseOptions::expand_macros`] gates
+//! whether [`crate::loadfile_parser::parse`] runs this pass, so ICWS 88 mode
+//! can stay strict.
+
+use alloc::{
+    borrow::ToOwned,
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::loadfile_parser::{ParseError, ParseErrorKind};
+
+/// Expands every `EQU` and `FOR`/`ROF` construct in `source`, returning the
+/// resulting plain loadfile text.
+///
+/// `EQU` is expanded first, as a left-to-right textual substitution; a
+/// label defined more than once by `EQU` has its later values appended to
+/// the earlier ones (separated by a comma), matching ICWS 94's multi-line
+/// `EQU` convention for defining a list through repeated statements.
+/// `FOR <count> ... ROF` is then expanded by repeating its body `count`
+/// times, substituting the loop's label with the 1-based iteration number
+/// on each copy.
+///
+/// # Errors
+///
+/// Returns a [`ParseError`] if a `FOR` has no matching `ROF`, a `ROF` has no
+/// matching `FOR`, or a `FOR` count isn't a valid integer literal.
+pub fn expand_macros(source: &str) -> Result<String, ParseError> {
+    let substituted = substitute_equ(source);
+    let lines: Vec<&str> = substituted.lines().collect();
+    let mut out = Vec::with_capacity(lines.len());
+    let mut idx = 0;
+    expand_block(&lines, &mut idx, &substituted, &mut out)?;
+    if idx < lines.len() {
+        return Err(error_at(
+            &substituted,
+            idx.saturating_add(1),
+            "ROF with no matching FOR".to_owned(),
+        ));
+    }
+    let mut result = out.join("\n");
+    if !result.is_empty() {
+        result.push('\n');
+    }
+    Ok(result)
+}
+
+/// Splits a line into an optional column-zero label and the remaining text.
+fn split_label(line: &str) -> (Option<&str>, &str) {
+    if line.is_empty() || line.starts_with(char::is_whitespace) {
+        return (None, line);
+    }
+    let ident_end = line
+        .find(|c: char| c.is_whitespace())
+        .unwrap_or(line.len());
+    (Some(&line[..ident_end]), line[ident_end..].trim_start())
+}
+
+/// If `rest` begins with the (case-insensitive) keyword, returns the
+/// trailing text.
+fn strip_keyword<'a>(rest: &'a str, keyword: &str) -> Option<&'a str> {
+    let bytes = rest.as_bytes();
+    if rest.len() < keyword.len()
+        || !rest[..keyword.len()].eq_ignore_ascii_case(keyword)
+    {
+        return None;
+    }
+    match bytes.get(keyword.len()) {
+        None => Some(""),
+        Some(c) if c.is_ascii_whitespace() => {
+            Some(rest[keyword.len()..].trim_start())
+        }
+        _ => None,
+    }
+}
+
+/// Performs `EQU` substitution as a left-to-right textual rewrite, dropping
+/// the defining lines.
+fn substitute_equ(source: &str) -> String {
+    let mut equs: BTreeMap<String, String> = BTreeMap::new();
+    let mut out = String::new();
+    for line in source.lines() {
+        // Detect an `EQU` directive against the raw, unsubstituted line:
+        // running substitutions first would rewrite the line's own label
+        // (if it happens to match an earlier `EQU` key) before split_label
+        // ever sees it.
+        let (label, rest) = split_label(line);
+        if let Some(label) = label {
+            if let Some(value) = strip_keyword(rest, "EQU") {
+                equs
+                    .entry(label.to_owned())
+                    .and_modify(|existing| {
+                        existing.push(',');
+                        existing.push_str(value);
+                    })
+                    .or_insert_with(|| value.to_owned());
+                continue;
+            }
+        }
+        out.push_str(&apply_substitutions(line, &equs));
+        out.push('\n');
+    }
+    out
+}
+
+/// Replaces every whole-identifier occurrence of a key in `substitutions`
+/// with its associated replacement text.
+fn apply_substitutions(
+    line: &str,
+    substitutions: &BTreeMap<String, String>,
+) -> String {
+    if substitutions.is_empty() {
+        return line.to_owned();
+    }
+    let mut out = String::with_capacity(line.len());
+    let mut chars = line.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        if c.is_ascii_alphabetic() || c == '_' {
+            let mut end = start + c.len_utf8();
+            while let Some(&(idx, next)) = chars.peek() {
+                if next.is_ascii_alphanumeric() || next == '_' {
+                    chars.next();
+                    end = idx + next.len_utf8();
+                } else {
+                    break;
+                }
+            }
+            let ident = &line[start..end];
+            match substitutions.get(ident) {
+                Some(replacement) => out.push_str(replacement),
+                None => out.push_str(ident),
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Returns `(loop_variable, count_expr)` if `line` opens a `FOR` block.
+fn for_directive(line: &str) -> Option<(Option<&str>, &str)> {
+    let (label, rest) = split_label(line);
+    if let Some(expr) = strip_keyword(rest, "FOR") {
+        return Some((label, expr));
+    }
+    None
+}
+
+/// Returns true if `line` is a (possibly whitespace-padded) `ROF`.
+fn is_rof(line: &str) -> bool {
+    line.trim().eq_ignore_ascii_case("ROF")
+}
+
+/// Expands a run of lines starting at `*idx`, stopping at a bare `ROF`
+/// (which is left unconsumed) or the end of `lines`.
+fn expand_block(
+    lines: &[&str],
+    idx: &mut usize,
+    original_for_errors: &str,
+    out: &mut Vec<String>,
+) -> Result<(), ParseError> {
+    while let Some(&line) = lines.get(*idx) {
+        if is_rof(line) {
+            return Ok(());
+        }
+        if let Some((loop_var, count_expr)) = for_directive(line) {
+            let for_line = idx.saturating_add(1);
+            let count: i64 =
+                count_expr.trim().parse().map_err(|_| {
+                    error_at(
+                        original_for_errors,
+                        for_line,
+                        format!(
+                            "invalid FOR count \"{}\"",
+                            count_expr.trim()
+                        ),
+                    )
+                })?;
+            *idx = for_line;
+            let body_start = *idx;
+            let mut depth = 1_u32;
+            let mut scan = body_start;
+            while let Some(&body_line) = lines.get(scan) {
+                if for_directive(body_line).is_some() {
+                    depth = depth.saturating_add(1);
+                } else if is_rof(body_line) {
+                    depth = depth.saturating_sub(1);
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                scan = scan.saturating_add(1);
+            }
+            if depth != 0 {
+                return Err(error_at(
+                    original_for_errors,
+                    for_line,
+                    "FOR with no matching ROF".to_owned(),
+                ));
+            }
+            let body = &lines[body_start..scan];
+            let count = count.max(0);
+            for i in 1..=count {
+                let substitutions: BTreeMap<String, String> = loop_var
+                    .map(|name| (name.to_owned(), i.to_string()))
+                    .into_iter()
+                    .collect();
+                let expanded_body: Vec<String> = body
+                    .iter()
+                    .map(|body_line| {
+                        apply_substitutions(body_line, &substitutions)
+                    })
+                    .collect();
+                let expanded_body: Vec<&str> =
+                    expanded_body.iter().map(String::as_str).collect();
+                let mut body_idx = 0;
+                expand_block(
+                    &expanded_body,
+                    &mut body_idx,
+                    original_for_errors,
+                    out,
+                )?;
+            }
+            *idx = scan.saturating_add(1);
+        } else {
+            out.push((*line).to_owned());
+            *idx = idx.saturating_add(1);
+        }
+    }
+    Ok(())
+}
+
+/// Builds a [`ParseError`] for a preprocessing failure at 1-based `line`.
+fn error_at(source: &str, line: usize, message: String) -> ParseError {
+    let source_line = source
+        .lines()
+        .nth(line.saturating_sub(1))
+        .unwrap_or("")
+        .to_owned();
+    ParseError {
+        line,
+        column: 1,
+        source_line,
+        message,
+        expected: Vec::new(),
+        kind: ParseErrorKind::Other,
+        suggestion: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn equ_is_substituted_textually() {
+        let source = "STEP EQU 4\nMOV.I $0, #STEP\n";
+        let expanded = expand_macros(source).unwrap();
+        assert_eq!(expanded, "MOV.I $0, #4\n");
+    }
+
+    #[test]
+    fn repeated_equ_on_the_same_label_concatenates_values() {
+        let source = "LIST EQU 1\nLIST EQU 2\nDAT.F #LIST, #0\n";
+        let expanded = expand_macros(source).unwrap();
+        assert_eq!(expanded, "DAT.F #1,2, #0\n");
+    }
+
+    #[test]
+    fn for_rof_expands_and_substitutes_the_loop_counter() {
+        let source = "i FOR 3\nDAT.F #i, #i\nROF\n";
+        let expanded = expand_macros(source).unwrap();
+        assert_eq!(expanded, "DAT.F #1, #1\nDAT.F #2, #2\nDAT.F #3, #3\n");
+    }
+
+    #[test]
+    fn nested_for_rof_expands_both_counters() {
+        let source = "i FOR 2\nj FOR 2\nDAT.F #i, #j\nROF\nROF\n";
+        let expanded = expand_macros(source).unwrap();
+        assert_eq!(
+            expanded,
+            "DAT.F #1, #1\nDAT.F #1, #2\nDAT.F #2, #1\nDAT.F #2, #2\n"
+        );
+    }
+
+    #[test]
+    fn unmatched_for_is_an_error() {
+        let source = "i FOR 3\nDAT.F #0, #0\n";
+        let err = expand_macros(source).unwrap_err();
+        assert_eq!(err.line, 1);
+    }
+
+    #[test]
+    fn unmatched_rof_is_an_error() {
+        let source = "DAT.F #0, #0\nROF\n";
+        let err = expand_macros(source).unwrap_err();
+        assert_eq!(err.line, 2);
+    }
+}
@@ -5,6 +5,10 @@
 //! standard extensions supported by the pMARS emulator.
 //!
 //! See also the ['94 ICWS draft](https://corewar.co.uk/standards/icws94.txt)
+//!
+//! `no_std` + `alloc`: this crate has no OS dependency, so it can be
+//! embedded on hosts with an allocator but no `std` (e.g. a WASM sandbox
+//! evaluating untrusted warriors).
 
 // Make clippy as annoying as possible
 #![deny(
@@ -94,10 +98,15 @@
 #![feature(lint_reasons)]
 // Prevent coverage reports from including lines in #[test]s
 #![cfg_attr(coverage_nightly, feature(no_coverage))]
+// Usable on constrained, allocator-only hosts with no OS to host `std`.
+// Left enabled under `cfg(test)` so `cargo test` keeps using the ordinary
+// std-backed test harness instead of a custom no_std test runner.
+#![cfg_attr(not(test), no_std)]
 
 // used to convert redcode enums to numerical values
 #[macro_use]
 extern crate num_derive;
+extern crate alloc;
 
 /// Standard representations for redcode types
 mod redcode;
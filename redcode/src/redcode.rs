@@ -1,3 +1,4 @@
+use alloc::{vec, vec::Vec};
 use core::fmt;
 
 use serde::{Deserialize, Serialize};
@@ -469,6 +470,251 @@ pub const fn default_modifiers(
     }
 }
 
+/// Which of an instruction's own fields are read or written, in the sense
+/// [`Modifier`]'s doc comments describe: e.g. an `ADD.AB` reads only the
+/// A-field of its source and writes only the B-field of its target.
+/// [`Modifier::I`] operates on whole instructions rather than individual
+/// fields, so it sets every flag, including `whole_instruction`.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct FieldAccess {
+    /// The A-field is read or written
+    pub a_field: bool,
+    /// The B-field is read or written
+    pub b_field: bool,
+    /// The whole instruction (opcode, modifier, both addressing modes, and
+    /// both fields), rather than a single field, is read or written
+    pub whole_instruction: bool,
+}
+
+/// Neither field nor the whole instruction is touched
+const NO_ACCESS: FieldAccess = FieldAccess {
+    a_field: false,
+    b_field: false,
+    whole_instruction: false,
+};
+
+/// Only the A-field is touched
+const A_FIELD: FieldAccess = FieldAccess { a_field: true, ..NO_ACCESS };
+
+/// Only the B-field is touched
+const B_FIELD: FieldAccess = FieldAccess { b_field: true, ..NO_ACCESS };
+
+/// Both fields, but not the opcode/modifier/addressing modes, are touched
+const BOTH_FIELDS: FieldAccess =
+    FieldAccess { a_field: true, b_field: true, ..NO_ACCESS };
+
+/// The whole instruction, including both fields, is touched
+const WHOLE_INSTRUCTION: FieldAccess =
+    FieldAccess { whole_instruction: true, ..BOTH_FIELDS };
+
+/// The union of two [`FieldAccess`] values, for opcodes (like the
+/// compare family) that consult both the pattern a [`Modifier`] reads from
+/// its source and the pattern it would otherwise write to its target.
+const fn union(lhs: FieldAccess, rhs: FieldAccess) -> FieldAccess {
+    FieldAccess {
+        a_field: lhs.a_field || rhs.a_field,
+        b_field: lhs.b_field || rhs.b_field,
+        whole_instruction: lhs.whole_instruction || rhs.whole_instruction,
+    }
+}
+
+/// Which of the current instruction's own fields supply the A-value/B-value
+/// operand pair `modifier` selects, per its doc comment.
+const fn modifier_reads(modifier: Modifier) -> FieldAccess {
+    match modifier {
+        Modifier::A | Modifier::AB => A_FIELD,
+        Modifier::B | Modifier::BA => B_FIELD,
+        Modifier::F | Modifier::X => BOTH_FIELDS,
+        Modifier::I => WHOLE_INSTRUCTION,
+    }
+}
+
+/// Which of the instruction pointed to by the B-pointer's fields `modifier`
+/// replaces on a write, per its doc comment.
+const fn modifier_writes(modifier: Modifier) -> FieldAccess {
+    match modifier {
+        Modifier::A | Modifier::BA => A_FIELD,
+        Modifier::B | Modifier::AB => B_FIELD,
+        Modifier::F | Modifier::X => BOTH_FIELDS,
+        Modifier::I => WHOLE_INSTRUCTION,
+    }
+}
+
+/// Which single field of the A-pointed instruction `modifier` selects for
+/// `Ldp`/`Stp`'s A-operand (the PSPACE index `Ldp` reads, or the value `Stp`
+/// reads to store). Unlike [`modifier_reads`], pMARS's `LDP`/`STP` never
+/// expand `F`/`X`/`I` to both fields or the whole instruction: undocumented
+/// in any ICWS standard, they're defined to behave exactly like `Modifier::B`
+/// for every modifier but `A`/`AB`.
+const fn ldp_stp_a_side(modifier: Modifier) -> FieldAccess {
+    match modifier {
+        Modifier::A | Modifier::AB => A_FIELD,
+        Modifier::B
+        | Modifier::BA
+        | Modifier::F
+        | Modifier::X
+        | Modifier::I => B_FIELD,
+    }
+}
+
+/// Which single field of the B-pointed instruction `modifier` selects for
+/// `Ldp`/`Stp`'s B-operand (the core field `Ldp` writes, or the PSPACE index
+/// `Stp` writes to). Unlike [`modifier_writes`], `F`/`X`/`I` collapse to the
+/// same single field as `B`/`AB` instead of expanding.
+const fn ldp_stp_b_side(modifier: Modifier) -> FieldAccess {
+    match modifier {
+        Modifier::A | Modifier::BA => A_FIELD,
+        Modifier::B
+        | Modifier::AB
+        | Modifier::F
+        | Modifier::X
+        | Modifier::I => B_FIELD,
+    }
+}
+
+/// The [`FieldAccess`] an instruction with `op` and `modifier` reads from
+/// its source and writes to its target, at the core-field level; pure
+/// PSPACE effects (as with `Stp`) aren't core-field writes.
+const fn field_access(
+    op: Opcode,
+    modifier: Modifier,
+) -> (FieldAccess, FieldAccess) {
+    match op {
+        // No operand fields are read or written in core.
+        Opcode::Dat | Opcode::Nop | Opcode::Jmp | Opcode::Spl => {
+            (NO_ACCESS, NO_ACCESS)
+        }
+        // Replace the B-target with a value derived from the A-instruction.
+        Opcode::Mov
+        | Opcode::Add
+        | Opcode::Sub
+        | Opcode::Mul
+        | Opcode::Div
+        | Opcode::Mod => (modifier_reads(modifier), modifier_writes(modifier)),
+        // Read the PSPACE value at the index the A-side selects and write it
+        // to the core field the B-side selects, never both fields or the
+        // whole instruction under any modifier (see `ldp_stp_a_side`/
+        // `ldp_stp_b_side`).
+        Opcode::Ldp => (ldp_stp_a_side(modifier), ldp_stp_b_side(modifier)),
+        // Read both the source and target patterns to compare them; no core
+        // write.
+        Opcode::Cmp | Opcode::Seq | Opcode::Sne | Opcode::Slt => {
+            let reads =
+                union(modifier_reads(modifier), modifier_writes(modifier));
+            (reads, NO_ACCESS)
+        }
+        // Read the core value the A-side selects and the PSPACE index the
+        // B-side selects; no core write, since Stp only ever writes to
+        // PSPACE.
+        Opcode::Stp => {
+            let reads =
+                union(ldp_stp_a_side(modifier), ldp_stp_b_side(modifier));
+            (reads, NO_ACCESS)
+        }
+        // Test the B-value without writing it.
+        Opcode::Jmz | Opcode::Jmn => (modifier_writes(modifier), NO_ACCESS),
+        // Decrement the B-target's field(s), then test the result.
+        Opcode::Djn => {
+            let target = modifier_writes(modifier);
+            (target, target)
+        }
+    }
+}
+
+/// How an instruction affects the program counter and task queue,
+/// independent of any data it reads or writes.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum FlowControl {
+    /// Queues the next instruction unconditionally
+    Sequential,
+    /// Queues the next instruction or the instruction after it, depending on
+    /// a runtime comparison: the `JMZ`/`JMN`/`DJN`/`SLT`/`CMP`/`SEQ`/`SNE`
+    /// family
+    Conditional,
+    /// Always queues a computed address instead of the next instruction:
+    /// `JMP`
+    Unconditional,
+    /// Queues both the next instruction and a computed address: `SPL`
+    Fork,
+    /// Removes the current process from the task queue and queues nothing:
+    /// `DAT`
+    Terminate,
+}
+
+/// The [`FlowControl`] category of `op`.
+const fn flow_control(op: Opcode) -> FlowControl {
+    match op {
+        Opcode::Dat => FlowControl::Terminate,
+        Opcode::Jmp => FlowControl::Unconditional,
+        Opcode::Spl => FlowControl::Fork,
+        Opcode::Jmz
+        | Opcode::Jmn
+        | Opcode::Djn
+        | Opcode::Slt
+        | Opcode::Cmp
+        | Opcode::Seq
+        | Opcode::Sne => FlowControl::Conditional,
+        Opcode::Mov
+        | Opcode::Add
+        | Opcode::Sub
+        | Opcode::Mul
+        | Opcode::Div
+        | Opcode::Mod
+        | Opcode::Nop
+        | Opcode::Ldp
+        | Opcode::Stp => FlowControl::Sequential,
+    }
+}
+
+/// Static facts about an instruction, derived from its opcode and modifier
+/// without stepping the VM or knowing what's stored at the addresses it
+/// reaches. Lets tooling such as an evolver, optimizer, or stillborn
+/// detector reason about an instruction's data and control-flow effects
+/// directly from decoded bytecode.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct InstructionInfo {
+    /// Which fields of the instruction addressed by the A-pointer this
+    /// instruction reads as its operand value(s)
+    pub reads: FieldAccess,
+    /// Which fields of the instruction addressed by the B-pointer this
+    /// instruction overwrites
+    pub writes: FieldAccess,
+    /// Whether the A-addressing mode mutates the pointer it resolves, i.e. a
+    /// predecrement or postincrement mode
+    pub a_mutates_pointer: bool,
+    /// Whether the B-addressing mode mutates the pointer it resolves
+    pub b_mutates_pointer: bool,
+    /// How this instruction affects the program counter and task queue
+    pub flow: FlowControl,
+}
+
+/// Whether resolving an operand under `mode` mutates the field of the cell
+/// it points to, as the pre- and post-increment addressing modes do.
+#[must_use]
+pub const fn addr_mode_mutates_pointer(mode: AddrMode) -> bool {
+    matches!(
+        mode,
+        AddrMode::PredecA
+            | AddrMode::PredecB
+            | AddrMode::PostincA
+            | AddrMode::PostincB
+    )
+}
+
+/// Reports the static data and control-flow facts about `instr`. See
+/// [`InstructionInfo`].
+#[must_use]
+pub const fn info(instr: Instruction) -> InstructionInfo {
+    let (reads, writes) = field_access(instr.opcode, instr.modifier);
+    InstructionInfo {
+        reads,
+        writes,
+        a_mutates_pointer: addr_mode_mutates_pointer(instr.a_addr_mode),
+        b_mutates_pointer: addr_mode_mutates_pointer(instr.b_addr_mode),
+        flow: flow_control(instr.opcode),
+    }
+}
+
 /// Utilities for enumerating and iterating over all valid redcode instructions
 pub mod test_utils {
     use super::*;
@@ -537,6 +783,89 @@ pub mod test_utils {
     }
 }
 
+/// A CoreWar rule set, determining which opcodes and addressing modes are
+/// valid.
+///
+/// Lets a decoder or a MARS reject instructions that are well-formed but
+/// outside the rules currently in effect, e.g. a `SEQ` showing up in an
+/// ICWS-88 round.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum Standard {
+    /// The ICWS '88 standard. Lacks `SEQ`, `SNE`, `NOP`, and the P-space
+    /// opcodes `LDP`/`STP`, as well as the indirect-A, predecrement-A, and
+    /// postincrement-A addressing modes (`*`, `{`, `}`).
+    Icws88,
+    /// The ICWS '94 draft standard: every opcode, modifier, and addressing
+    /// mode this crate defines.
+    Icws94Draft,
+    /// pMARS's extended rule set. Allows everything [`Standard::Icws94Draft`]
+    /// allows; kept distinct from it so an opcode or mode that's a pMARS
+    /// extension but not part of '94 has somewhere to be gated if one is
+    /// ever added to this crate.
+    PMarsExtended,
+}
+
+impl Standard {
+    /// Opcodes valid under this standard.
+    #[must_use]
+    pub fn opcodes(self) -> Vec<Opcode> {
+        test_utils::OPCODES
+            .iter()
+            .copied()
+            .filter(|&op| self.allows_opcode(op))
+            .collect()
+    }
+
+    /// Addressing modes valid under this standard.
+    #[must_use]
+    pub fn addr_modes(self) -> Vec<AddrMode> {
+        test_utils::ADDR_MODES
+            .iter()
+            .copied()
+            .filter(|&mode| self.allows_addr_mode(mode))
+            .collect()
+    }
+
+    /// Whether `op` is valid under this standard.
+    #[must_use]
+    pub const fn allows_opcode(self, op: Opcode) -> bool {
+        !matches!(
+            (self, op),
+            (
+                Self::Icws88,
+                Opcode::Seq
+                    | Opcode::Sne
+                    | Opcode::Nop
+                    | Opcode::Ldp
+                    | Opcode::Stp,
+            )
+        )
+    }
+
+    /// Whether `mode` is valid under this standard.
+    #[must_use]
+    pub const fn allows_addr_mode(self, mode: AddrMode) -> bool {
+        !matches!(
+            (self, mode),
+            (
+                Self::Icws88,
+                AddrMode::IndirectA | AddrMode::PredecA | AddrMode::PostincA,
+            )
+        )
+    }
+
+    /// Whether every opcode, modifier, and addressing mode `instr` uses is
+    /// valid under this standard. Modifiers are unrestricted across all
+    /// three standards, so only `instr`'s opcode and addressing modes are
+    /// checked.
+    #[must_use]
+    pub const fn allows(self, instr: Instruction) -> bool {
+        self.allows_opcode(instr.opcode)
+            && self.allows_addr_mode(instr.a_addr_mode)
+            && self.allows_addr_mode(instr.b_addr_mode)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use coverage_helper::test;
@@ -625,4 +954,180 @@ mod tests {
             assert!(mode.to_u8().is_some())
         }
     }
+
+    #[test]
+    fn icws_88_excludes_newer_opcodes_and_modes() {
+        assert!(!Standard::Icws88.allows_opcode(Opcode::Seq));
+        assert!(!Standard::Icws88.allows_opcode(Opcode::Sne));
+        assert!(!Standard::Icws88.allows_opcode(Opcode::Nop));
+        assert!(!Standard::Icws88.allows_opcode(Opcode::Ldp));
+        assert!(!Standard::Icws88.allows_opcode(Opcode::Stp));
+        assert!(!Standard::Icws88.allows_addr_mode(AddrMode::IndirectA));
+        assert!(!Standard::Icws88.allows_addr_mode(AddrMode::PredecA));
+        assert!(!Standard::Icws88.allows_addr_mode(AddrMode::PostincA));
+
+        assert!(Standard::Icws88.allows_opcode(Opcode::Mov));
+        assert!(Standard::Icws88.allows_addr_mode(AddrMode::IndirectB));
+    }
+
+    #[test]
+    fn icws_94_draft_and_pmars_extended_allow_everything() {
+        for standard in [Standard::Icws94Draft, Standard::PMarsExtended] {
+            for op in test_utils::OPCODES {
+                assert!(standard.allows_opcode(op));
+            }
+            for mode in test_utils::ADDR_MODES {
+                assert!(standard.allows_addr_mode(mode));
+            }
+        }
+    }
+
+    #[test]
+    fn allows_checks_opcode_and_both_addressing_modes() {
+        let instr = Instruction {
+            opcode: Opcode::Seq,
+            modifier: Modifier::F,
+            a_addr_mode: AddrMode::Direct,
+            b_addr_mode: AddrMode::Direct,
+        };
+        assert!(!Standard::Icws88.allows(instr));
+        assert!(Standard::Icws94Draft.allows(instr));
+    }
+
+    #[test]
+    fn info_reports_add_ab_semantics() {
+        let instr = Instruction {
+            opcode: Opcode::Add,
+            modifier: Modifier::AB,
+            a_addr_mode: AddrMode::Immediate,
+            b_addr_mode: AddrMode::Direct,
+        };
+        let reported = info(instr);
+        assert_eq!(
+            reported.reads,
+            FieldAccess { a_field: true, ..FieldAccess::default() }
+        );
+        assert_eq!(
+            reported.writes,
+            FieldAccess { b_field: true, ..FieldAccess::default() }
+        );
+        assert_eq!(reported.flow, FlowControl::Sequential);
+    }
+
+    #[test]
+    fn info_reports_mov_i_semantics() {
+        let instr = Instruction {
+            opcode: Opcode::Mov,
+            modifier: Modifier::I,
+            a_addr_mode: AddrMode::Direct,
+            b_addr_mode: AddrMode::Direct,
+        };
+        let reported = info(instr);
+        assert!(reported.reads.whole_instruction);
+        assert!(reported.reads.a_field && reported.reads.b_field);
+        assert_eq!(reported.writes, reported.reads);
+    }
+
+    #[test]
+    fn info_reports_ldp_single_field_access_under_every_modifier() {
+        let access_of = |modifier| {
+            let instr = Instruction {
+                opcode: Opcode::Ldp,
+                modifier,
+                a_addr_mode: AddrMode::Direct,
+                b_addr_mode: AddrMode::Direct,
+            };
+            let reported = info(instr);
+            (reported.reads, reported.writes)
+        };
+        assert_eq!(
+            access_of(Modifier::F),
+            (
+                FieldAccess { b_field: true, ..FieldAccess::default() },
+                FieldAccess { b_field: true, ..FieldAccess::default() }
+            )
+        );
+        assert_eq!(
+            access_of(Modifier::I),
+            (
+                FieldAccess { b_field: true, ..FieldAccess::default() },
+                FieldAccess { b_field: true, ..FieldAccess::default() }
+            )
+        );
+        assert_eq!(
+            access_of(Modifier::A),
+            (
+                FieldAccess { a_field: true, ..FieldAccess::default() },
+                FieldAccess { a_field: true, ..FieldAccess::default() }
+            )
+        );
+    }
+
+    #[test]
+    fn info_reports_stp_single_field_reads_and_no_core_write() {
+        let reads_of = |modifier| {
+            let instr = Instruction {
+                opcode: Opcode::Stp,
+                modifier,
+                a_addr_mode: AddrMode::Direct,
+                b_addr_mode: AddrMode::Direct,
+            };
+            info(instr).reads
+        };
+        assert_eq!(
+            reads_of(Modifier::F),
+            FieldAccess { b_field: true, ..FieldAccess::default() }
+        );
+        assert_eq!(
+            reads_of(Modifier::I),
+            FieldAccess { b_field: true, ..FieldAccess::default() }
+        );
+        assert_eq!(
+            reads_of(Modifier::AB),
+            FieldAccess {
+                a_field: true,
+                b_field: true,
+                ..FieldAccess::default()
+            }
+        );
+        let instr = Instruction {
+            opcode: Opcode::Stp,
+            modifier: Modifier::I,
+            a_addr_mode: AddrMode::Direct,
+            b_addr_mode: AddrMode::Direct,
+        };
+        assert_eq!(info(instr).writes, FieldAccess::default());
+    }
+
+    #[test]
+    fn info_classifies_flow_control_by_opcode() {
+        let flow_of = |opcode| {
+            info(Instruction { opcode, ..Instruction::default() }).flow
+        };
+        assert_eq!(flow_of(Opcode::Dat), FlowControl::Terminate);
+        assert_eq!(flow_of(Opcode::Jmp), FlowControl::Unconditional);
+        assert_eq!(flow_of(Opcode::Spl), FlowControl::Fork);
+        assert_eq!(flow_of(Opcode::Djn), FlowControl::Conditional);
+        assert_eq!(flow_of(Opcode::Nop), FlowControl::Sequential);
+    }
+
+    #[test]
+    fn addr_mode_mutates_pointer_flags_pre_and_post_increment() {
+        for mode in [
+            AddrMode::PredecA,
+            AddrMode::PredecB,
+            AddrMode::PostincA,
+            AddrMode::PostincB,
+        ] {
+            assert!(addr_mode_mutates_pointer(mode));
+        }
+        for mode in [
+            AddrMode::Immediate,
+            AddrMode::Direct,
+            AddrMode::IndirectA,
+            AddrMode::IndirectB,
+        ] {
+            assert!(!addr_mode_mutates_pointer(mode));
+        }
+    }
 }
@@ -1,3 +1,9 @@
+use alloc::{
+    collections::BTreeMap,
+    string::String,
+    vec,
+    vec::Vec,
+};
 use core::convert::Into;
 
 use crate::{CompleteInstruction, FieldValue, Instruction, Warrior};
@@ -17,6 +23,12 @@ pub struct RelaxedCompleteInstruction {
 impl RelaxedCompleteInstruction {
     /// Convert into a [`CompleteInstruction`], possibly by evaluating fields
     /// modulo `core_size`
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `core_size` is zero or not less than `u32::MAX`.
+    /// Use [`RelaxedCompleteInstruction::try_normalize`] to handle an
+    /// untrusted `core_size` without panicking.
     pub fn normalize<T>(&self, core_size: T) -> CompleteInstruction
     where T: Into<u64> + Copy {
         CompleteInstruction {
@@ -25,11 +37,30 @@ impl RelaxedCompleteInstruction {
             b_field: normalize(self.b_field, core_size),
         }
     }
+
+    /// Convert into a [`CompleteInstruction`], possibly by evaluating fields
+    /// modulo `core_size`
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`NormalizeError`] if `core_size` is zero or not less than
+    /// `u32::MAX`, rather than panicking.
+    pub fn try_normalize<T>(
+        &self,
+        core_size: T,
+    ) -> Result<CompleteInstruction, NormalizeError>
+    where T: Into<u64> + Copy {
+        Ok(CompleteInstruction {
+            instr: self.instr,
+            a_field: try_normalize(self.a_field, core_size)?,
+            b_field: try_normalize(self.b_field, core_size)?,
+        })
+    }
 }
 
 /// A [`Warrior`] with [`RelaxedCompleteInstruction`]s that allow field values
 /// less than zero or greater than `core_size`
-#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct RelaxedWarrior {
     /// A sequence of redcode instructions
     pub code: Vec<RelaxedCompleteInstruction>,
@@ -41,11 +72,36 @@ pub struct RelaxedWarrior {
     /// An optional identifier that warriors may optionally specify to indicate
     /// that it should share it's PSPACE with other warriors with the same pin.
     pub pin: Option<i64>,
+    /// The warrior's name, taken from a `;name` metadata comment.
+    ///
+    /// Only populated when the parser is run with metadata parsing enabled.
+    pub name: Option<String>,
+    /// The warrior's author, taken from an `;author` metadata comment.
+    ///
+    /// Only populated when the parser is run with metadata parsing enabled.
+    pub author: Option<String>,
+    /// Free-form strategy text, one entry per `;strategy` metadata comment
+    /// line, in the order they appeared in the source.
+    ///
+    /// Only populated when the parser is run with metadata parsing enabled.
+    pub strategy: Vec<String>,
+    /// Any other metadata comments, keyed by the lowercased identifier that
+    /// followed the `;`.  Values are accumulated in source order, since a
+    /// key may appear on more than one comment line.
+    ///
+    /// Only populated when the parser is run with metadata parsing enabled.
+    pub metadata: BTreeMap<String, Vec<String>>,
 }
 
 impl RelaxedWarrior {
     /// Convert into a [`Warrior`] consisting of [`CompleteInstruction`]s,
     /// possibly by evaluating fields modulo `core_size`
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `core_size` is zero or not less than `u32::MAX`.
+    /// Use [`RelaxedWarrior::try_normalize`] to handle an untrusted
+    /// `core_size` without panicking.
     pub fn normalize<T>(&self, core_size: T) -> Warrior
     where T: Into<u64> + Copy {
         let code = self
@@ -59,6 +115,30 @@ impl RelaxedWarrior {
             pin: self.pin,
         }
     }
+
+    /// Convert into a [`Warrior`] consisting of [`CompleteInstruction`]s,
+    /// possibly by evaluating fields modulo `core_size`
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`NormalizeError`] if `core_size` is zero or not less than
+    /// `u32::MAX`, rather than panicking.
+    pub fn try_normalize<T>(
+        &self,
+        core_size: T,
+    ) -> Result<Warrior, NormalizeError>
+    where T: Into<u64> + Copy {
+        let code = self
+            .code
+            .iter()
+            .map(|insn| insn.try_normalize(core_size))
+            .collect::<Result<_, _>>()?;
+        Ok(Warrior {
+            code,
+            start: try_normalize(self.start, core_size)?,
+            pin: self.pin,
+        })
+    }
 }
 
 impl Default for RelaxedWarrior {
@@ -67,34 +147,100 @@ impl Default for RelaxedWarrior {
             code: vec![RelaxedCompleteInstruction::default()],
             start: 0,
             pin: None,
+            name: None,
+            author: None,
+            strategy: Vec::new(),
+            metadata: BTreeMap::new(),
+        }
+    }
+}
+
+/// Why [`try_normalize`] couldn't evaluate a value modulo `core_size`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum NormalizeError {
+    /// `core_size` was zero, so there's no modulus to normalize against
+    ZeroCoreSize,
+    /// `core_size` was `u32::MAX` or greater
+    CoreSizeTooLarge {
+        /// The out-of-range `core_size` that was supplied
+        core_size: u64,
+    },
+}
+
+impl core::fmt::Display for NormalizeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::ZeroCoreSize => write!(f, "core_size must not be zero"),
+            Self::CoreSizeTooLarge { core_size } => {
+                write!(
+                    f,
+                    "core_size {core_size} is not less than u32::MAX"
+                )
+            }
         }
     }
 }
 
+impl core::error::Error for NormalizeError {}
+
 /// Evaluate a value as if it is a core offset, wrapping around at `core_size`.
 ///
 /// # Panics
 ///
-/// Will panic if `core_size` is less than 0 or greater than `u32::MAX`.
+/// Will panic if `core_size` is zero or not less than `u32::MAX`. Use
+/// [`try_normalize`] to handle an untrusted `core_size` without panicking.
 pub fn normalize<T, K>(value: K, core_size: T) -> FieldValue
 where
     T: Into<u64> + Copy,
     K: Into<i64>,
 {
-    assert!(core_size.into() < u64::from(u32::MAX));
-    assert!(core_size.into() > 0);
-    let core_size: i64 = core_size.into().try_into().unwrap_or(0);
+    match try_normalize(value, core_size) {
+        Ok(normalized) => normalized,
+        Err(NormalizeError::ZeroCoreSize) => {
+            panic!("core_size must not be zero")
+        }
+        Err(NormalizeError::CoreSizeTooLarge { core_size }) => {
+            panic!("core_size {core_size} is not less than u32::MAX")
+        }
+    }
+}
+
+/// Evaluate a value as if it is a core offset, wrapping around at `core_size`.
+///
+/// # Errors
+///
+/// Returns [`NormalizeError::ZeroCoreSize`] if `core_size` is zero, or
+/// [`NormalizeError::CoreSizeTooLarge`] if it's `u32::MAX` or greater,
+/// rather than panicking.
+pub fn try_normalize<T, K>(
+    value: K,
+    core_size: T,
+) -> Result<FieldValue, NormalizeError>
+where
+    T: Into<u64> + Copy,
+    K: Into<i64>,
+{
+    let core_size_u64: u64 = core_size.into();
+    if core_size_u64 == 0 {
+        return Err(NormalizeError::ZeroCoreSize);
+    }
+    if core_size_u64 >= u64::from(u32::MAX) {
+        return Err(NormalizeError::CoreSizeTooLarge {
+            core_size: core_size_u64,
+        });
+    }
+    let core_size: i64 = core_size_u64.try_into().unwrap_or(0);
     let mut v = value.into();
     while v < 0 {
         v = v.wrapping_add(core_size);
     }
     let normalized = v.checked_rem(core_size).unwrap_or(0);
-    normalized.try_into().unwrap_or(0)
+    Ok(normalized.try_into().unwrap_or(0))
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{RelaxedCompleteInstruction, RelaxedWarrior};
+    use super::{NormalizeError, RelaxedCompleteInstruction, RelaxedWarrior};
     use crate::Instruction;
 
     #[test]
@@ -142,6 +288,7 @@ mod tests {
             }],
             start: 0,
             pin: None,
+            ..RelaxedWarrior::default()
         };
         let _normalized = war.normalize(0_u32);
     }
@@ -157,7 +304,65 @@ mod tests {
             }],
             start: 0,
             pin: None,
+            ..RelaxedWarrior::default()
         };
         let _normalized = war.normalize(u32::max as u64 + 1);
     }
+
+    #[test]
+    fn try_normalize_matches_normalize_on_valid_input() {
+        let i = RelaxedCompleteInstruction {
+            instr: Instruction::default(),
+            a_field: -10,
+            b_field: 20,
+        };
+        let normalized = i.try_normalize(15_u32).unwrap();
+        assert_eq!(normalized.a_field, 5);
+        assert_eq!(normalized.b_field, 5);
+    }
+
+    #[test]
+    fn try_normalize_instr_with_zero_coresize_is_an_error() {
+        let i = RelaxedCompleteInstruction {
+            instr: Instruction::default(),
+            a_field: 0,
+            b_field: 0,
+        };
+        assert_eq!(
+            i.try_normalize(0_u32),
+            Err(NormalizeError::ZeroCoreSize)
+        );
+    }
+
+    #[test]
+    fn try_normalize_instr_with_massive_coresize_is_an_error() {
+        let i = RelaxedCompleteInstruction {
+            instr: Instruction::default(),
+            a_field: 0,
+            b_field: 0,
+        };
+        let core_size = u32::max as u64 + 1;
+        assert_eq!(
+            i.try_normalize(core_size),
+            Err(NormalizeError::CoreSizeTooLarge { core_size })
+        );
+    }
+
+    #[test]
+    fn try_normalize_warrior_with_zero_coresize_is_an_error() {
+        let war = RelaxedWarrior {
+            code: vec![RelaxedCompleteInstruction {
+                instr: Instruction::default(),
+                a_field: 0,
+                b_field: 0,
+            }],
+            start: 0,
+            pin: None,
+            ..RelaxedWarrior::default()
+        };
+        assert_eq!(
+            war.try_normalize(0_u32),
+            Err(NormalizeError::ZeroCoreSize)
+        );
+    }
 }